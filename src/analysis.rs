@@ -0,0 +1,189 @@
+//! Analysis of a finished solve against the optimal solution.
+
+use crate::board::{move_tile, Board, Operation};
+use crate::heuristic::{Heuristic, PATTERN_MAX_CELLS};
+use crate::solver::{solve_cancellable, Solution};
+use crate::timing::{long_pauses, move_intervals};
+use std::{error::Error, sync::atomic::AtomicBool, time::Duration};
+
+/// Biggest boards analyzed, every position is solved with the pattern database of the goal.
+pub const ANALYSIS_MAX_CELLS: usize = PATTERN_MAX_CELLS;
+
+pub struct Analysis {
+    /// Optimal distance to solved before each move, and after the last one.
+    distances: Vec<usize>,
+    moves: Vec<Operation>,
+    move_times: Vec<Duration>,
+    time: Duration,
+}
+
+impl Analysis {
+    /// Solves every position of the solve into `goal`, which takes a while on bigger boards.
+    /// Positions reached by following the previous optimal solution aren't solved again.
+    /// Returns `None` once `cancel` is set.
+    pub fn new(
+        scramble: &Board,
+        goal: &Board,
+        moves: &[Operation],
+        move_times: &[Duration],
+        time: Duration,
+        cancel: &AtomicBool,
+    ) -> Result<Option<Analysis>, Box<dyn Error>> {
+        if scramble.width() * scramble.height() > ANALYSIS_MAX_CELLS {
+            return Err(format!(
+                "Only boards of up to {} cells are analyzed",
+                ANALYSIS_MAX_CELLS
+            )
+            .into());
+        }
+
+        let solve = |board: &Board| -> Result<Option<Solution>, Box<dyn Error>> {
+            solve_cancellable(board, goal, Heuristic::PatternDatabase, cancel, &mut |_| {})
+        };
+
+        let mut board = scramble.clone();
+        let mut solution = match solve(&board)? {
+            Some(solution) => solution.moves,
+            None => return Ok(None),
+        };
+        let mut distances = vec![solution.len()];
+
+        for operation in moves.iter() {
+            board = move_tile(&board, *operation);
+
+            if solution.first() == Some(operation) {
+                solution.remove(0);
+            } else {
+                solution = match solve(&board)? {
+                    Some(solution) => solution.moves,
+                    None => return Ok(None),
+                };
+            }
+            distances.push(solution.len());
+        }
+
+        Ok(Some(Analysis {
+            distances,
+            moves: moves.to_vec(),
+            move_times: move_times.to_vec(),
+            time,
+        }))
+    }
+
+    pub fn moves(&self) -> &[Operation] {
+        &self.moves
+    }
+
+    pub fn optimal_length(&self) -> usize {
+        self.distances[0]
+    }
+
+    /// Optimal length as a percentage of the moves made.
+    pub fn efficiency(&self) -> f64 {
+        if self.moves.is_empty() {
+            return 100.0;
+        }
+
+        self.optimal_length() as f64 / self.moves.len() as f64 * 100.0
+    }
+
+    /// Turns per second, moves made per second of the solve.
+    pub fn tps(&self) -> f64 {
+        let seconds = self.time.as_secs_f64();
+
+        if seconds == 0.0 {
+            return 0.0;
+        }
+
+        self.moves.len() as f64 / seconds
+    }
+
+    /// For each move, whether it took the board further from solved.
+    pub fn wasted(&self) -> Vec<bool> {
+        self.distances
+            .windows(2)
+            .map(|pair| pair[1] > pair[0])
+            .collect()
+    }
+
+    /// Time before each move.
+    pub fn intervals(&self) -> Vec<Duration> {
+        move_intervals(&self.move_times)
+    }
+
+    /// Indices of the moves that came after a long pause.
+    pub fn long_pauses(&self) -> Vec<usize> {
+        long_pauses(&self.intervals())
+    }
+
+    /// Time spent on wasted moves, each counted from the move before it.
+    pub fn wasted_time(&self) -> Duration {
+        if self.move_times.len() != self.moves.len() {
+            return Duration::from_secs(0);
+        }
+
+        self.wasted()
+            .iter()
+            .enumerate()
+            .filter(|(_, wasted)| **wasted)
+            .map(|(index, _)| match index {
+                0 => self.move_times[0],
+                _ => self.move_times[index] - self.move_times[index - 1],
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::parse_moves;
+
+    #[test]
+    fn analysis_should_find_wasted_moves() -> Result<(), Box<dyn Error>> {
+        let scramble: Board = "1,2,3/4,5,6/0,7,8".parse()?;
+        // Up and back down first, then the optimal solution.
+        let moves = parse_moves("swaa")?;
+        let move_times: Vec<Duration> = [1000, 1500, 3500, 4000]
+            .iter()
+            .map(|ms| Duration::from_millis(*ms))
+            .collect();
+
+        let goal = Board::solved(3, 3);
+        let analysis = Analysis::new(
+            &scramble,
+            &goal,
+            &moves,
+            &move_times,
+            Duration::from_secs(4),
+            &AtomicBool::new(false),
+        )?
+        .unwrap();
+        assert_eq!(analysis.optimal_length(), 2);
+        assert_eq!(analysis.wasted(), vec![true, false, false, false]);
+        assert_eq!(analysis.wasted_time(), Duration::from_millis(1000));
+        assert!((analysis.efficiency() - 50.0).abs() < 1e-9);
+        assert!((analysis.tps() - 1.0).abs() < 1e-9);
+
+        let cancelled = Analysis::new(
+            &scramble,
+            &goal,
+            &moves,
+            &move_times,
+            Duration::from_secs(4),
+            &AtomicBool::new(true),
+        )?;
+        assert!(cancelled.is_none());
+        assert!(Analysis::new(
+            &Board::solved(5, 5),
+            &Board::solved(5, 5),
+            &[],
+            &[],
+            Duration::from_secs(0),
+            &AtomicBool::new(false),
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use crate::cli::{EXIT_FAILURE, EXIT_SUCCESS};
+use rust_15_puzzle_cli::{format_moves, scramble::OPTIMAL_SCRAMBLE_MAX_CELLS, solve, Board};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Instant,
+};
+
+/// Solves every board of `input` (stdin when `None`) on `threads` workers and writes one JSON
+/// object per board. Boards already present in `output` are skipped, so an interrupted run can
+/// be resumed by running the same command again. Their errors still count in the exit code.
+pub fn run_batch(
+    input: Option<&Path>,
+    output: Option<&Path>,
+    threads: usize,
+) -> Result<i32, Box<dyn Error>> {
+    let lines: Vec<String> = match input {
+        Some(path) => BufReader::new(fs::File::open(path)?)
+            .lines()
+            .collect::<Result<_, _>>()?,
+        None => io::stdin().lock().lines().collect::<Result<_, _>>()?,
+    };
+
+    let (done, mut writer): (HashMap<String, bool>, Box<dyn Write>) = match output {
+        Some(path) => {
+            let existing = if path.exists() {
+                fs::read_to_string(path)?
+            } else {
+                String::new()
+            };
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            if !existing.is_empty() && !existing.ends_with('\n') {
+                // The previous run was killed in the middle of a line.
+                writeln!(file)?;
+            }
+
+            (completed_boards(&existing), Box::new(file))
+        }
+        None => (HashMap::new(), Box::new(io::stdout())),
+    };
+
+    let (skipped, jobs): (Vec<(usize, String)>, Vec<_>) = lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim().to_string()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .partition(|(_, line)| done.contains_key(&normalize(line)));
+
+    let total = jobs.len();
+    let failed_before = skipped
+        .iter()
+        .any(|(_, line)| done.get(&normalize(line)) == Some(&true));
+    let skipped = skipped.len();
+    let jobs = Arc::new(Mutex::new(VecDeque::from(jobs)));
+    let (tx, rx) = mpsc::channel();
+
+    for _ in 0..threads.max(1) {
+        let jobs = jobs.clone();
+        let tx = tx.clone();
+
+        thread::spawn(move || loop {
+            let job = jobs.lock().unwrap().pop_front();
+            let (line_number, line) = match job {
+                Some(job) => job,
+                None => return,
+            };
+
+            if tx.send(solve_line(line_number, &line)).is_err() {
+                return;
+            }
+        });
+    }
+    drop(tx);
+
+    let show_progress = termion::is_tty(&io::stderr());
+    let mut exit_code = if failed_before {
+        EXIT_FAILURE
+    } else {
+        EXIT_SUCCESS
+    };
+
+    for (finished, result) in rx.iter().enumerate() {
+        if result.get("error").is_some() {
+            exit_code = EXIT_FAILURE;
+        }
+
+        writeln!(writer, "{}", result)?;
+        writer.flush()?;
+
+        if show_progress {
+            eprint!(
+                "\r{}/{} boards solved ({} already done)",
+                finished + 1,
+                total,
+                skipped
+            );
+        }
+    }
+
+    if show_progress {
+        eprintln!();
+    }
+
+    Ok(exit_code)
+}
+
+/// Solves one board optimally. Bigger boards than the optimal solver handles are reported as
+/// errors, their search would never end.
+fn solve_line(line_number: usize, line: &str) -> Value {
+    let start_time = Instant::now();
+    let result = line.parse::<Board>().and_then(|board| {
+        if board.width() * board.height() > OPTIMAL_SCRAMBLE_MAX_CELLS {
+            return Err(format!(
+                "Boards can have at most {} cells to be solved optimally",
+                OPTIMAL_SCRAMBLE_MAX_CELLS
+            )
+            .into());
+        }
+
+        solve(&board).map(|solution| (board, solution))
+    });
+
+    match result {
+        Ok((board, solution)) => json!({
+            "line": line_number,
+            "board": board.to_string(),
+            "moves": format_moves(&solution.moves),
+            "length": solution.moves.len(),
+            "nodes_expanded": solution.nodes_expanded,
+            "time_ms": start_time.elapsed().as_millis() as u64,
+        }),
+        Err(e) => json!({
+            "line": line_number,
+            "board": line,
+            "error": e.to_string(),
+        }),
+    }
+}
+
+/// Boards are compared in their canonical form, so `1, 2/3, 0` matches `1,2/3,0`.
+fn normalize(line: &str) -> String {
+    line.parse::<Board>()
+        .map(|board| board.to_string())
+        .unwrap_or_else(|_| line.to_string())
+}
+
+/// Boards already written by a previous run, and whether they failed. Unreadable lines are
+/// ignored and solved again.
+fn completed_boards(output: &str) -> HashMap<String, bool> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|result| {
+            let board = normalize(result.get("board")?.as_str()?);
+
+            Some((board, result.get("error").is_some()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completed_boards_should_skip_broken_lines() {
+        let output = concat!(
+            "{\"line\":1,\"board\":\"1,2,3/4,5,6/7,0,8\",\"moves\":\"a\"}\n",
+            "{\"line\":2,\"board\":\"2,1,3/4,5,6/7,8,0\",\"error\":\"Board is not solvable!\"}\n",
+            "{\"line\":3,\"board\":\"1,2,3/4,5,6/0,7"
+        );
+
+        let done = completed_boards(output);
+        assert_eq!(done.len(), 2);
+        assert_eq!(done.get("1,2,3/4,5,6/7,0,8"), Some(&false));
+        // Failed boards aren't solved again, but they are remembered as failures.
+        assert_eq!(
+            done.get(&normalize("2, 1, 3 / 4, 5, 6 / 7, 8, 0")),
+            Some(&true)
+        );
+    }
+
+    #[test]
+    fn solve_line_should_report_errors() {
+        let result = solve_line(1, "1,2,3/4,5,6/7,0,8");
+        assert_eq!(result["moves"], "a");
+        assert_eq!(result["length"], 1);
+
+        let result = solve_line(2, "1,2,3/4,5,6/8,7,0");
+        assert!(result.get("error").is_some());
+
+        let result = solve_line(3, "not a board");
+        assert!(result.get("error").is_some());
+
+        let result = solve_line(4, &Board::solved(5, 5).to_string());
+        assert!(result.get("error").is_some());
+    }
+}
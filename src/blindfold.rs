@@ -0,0 +1,141 @@
+//! Blindfold solves: the scramble is shown for memorizing, then solved with the tiles hidden.
+
+use crate::game::{GameData, GameMode, GameState};
+use crate::stats::SolveRecord;
+use std::time::{Duration, Instant};
+
+pub struct Blindfold {
+    memo_limit: Duration,
+    shown_at: Instant,
+    memo_time: Option<Duration>,
+}
+
+impl Blindfold {
+    /// Starts memorizing the scramble of `game_data`, for at most `memo_limit`.
+    pub fn new(memo_limit: Duration, game_data: &mut GameData) -> Blindfold {
+        game_data.mode = GameMode::Blindfold;
+
+        Blindfold {
+            memo_limit,
+            shown_at: Instant::now(),
+            memo_time: None,
+        }
+    }
+
+    /// Time spent memorizing, `None` while the scramble is still shown.
+    pub fn memo_time(&self) -> Option<Duration> {
+        self.memo_time
+    }
+
+    pub fn memo_left(&self) -> Duration {
+        self.memo_limit.saturating_sub(self.shown_at.elapsed())
+    }
+
+    /// Whether the tiles are hidden: after memorizing, until the solve is finished.
+    pub fn hidden(&self, game_data: &GameData) -> bool {
+        self.memo_time.is_some()
+            && (game_data.game_state == GameState::PLAYING
+                || game_data.game_state == GameState::PAUSED)
+    }
+
+    /// Ends memorizing on the first move, or when the memo time is up, which starts the clock of
+    /// the solve. Returns whether it just ended.
+    pub fn check_memo(&mut self, game_data: &mut GameData) -> bool {
+        if self.memo_time.is_some() {
+            return false;
+        }
+
+        if game_data.game_state != GameState::INIT {
+            // The solve clock started with the first move, memorizing ended just before.
+            self.memo_time = Some(
+                self.shown_at
+                    .elapsed()
+                    .saturating_sub(game_data.elapsed_time())
+                    .min(self.memo_limit),
+            );
+        } else if self.shown_at.elapsed() >= self.memo_limit {
+            self.memo_time = Some(self.memo_limit);
+            game_data.start_time = Instant::now();
+            game_data.game_state = GameState::PLAYING;
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    /// The finished solve, its time includes memorizing.
+    pub fn to_record(&self, game_data: &GameData) -> SolveRecord {
+        let memo_time = self.memo_time.unwrap_or_default();
+        let record = SolveRecord::from_game(game_data);
+
+        SolveRecord {
+            memo_time: Some(memo_time),
+            time: memo_time + record.time,
+            ..record
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Operation;
+    use crate::game::{finish_blindfold, handle_move_operation};
+    use crate::scramble::Difficulty;
+    use std::error::Error;
+
+    fn blindfold(memo_limit: Duration) -> Result<(Blindfold, GameData), Box<dyn Error>> {
+        let mut game_data =
+            GameData::from_scramble("1,2,3/4,5,6/0,7,8".parse()?, Difficulty::Easy, None);
+        let blindfold = Blindfold::new(memo_limit, &mut game_data);
+
+        Ok((blindfold, game_data))
+    }
+
+    #[test]
+    fn blindfold_should_hide_tiles_until_finished() -> Result<(), Box<dyn Error>> {
+        let (mut blindfold, mut game_data) = blindfold(Duration::from_secs(60))?;
+        assert!(!blindfold.check_memo(&mut game_data));
+        assert!(!blindfold.hidden(&game_data));
+
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        assert!(blindfold.check_memo(&mut game_data));
+        assert!(blindfold.memo_time().unwrap() < Duration::from_secs(60));
+        assert!(blindfold.hidden(&game_data));
+
+        // Solving doesn't give it away, only finishing reveals the board.
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        assert!(game_data.game_state == GameState::PLAYING);
+        finish_blindfold(&mut game_data);
+        assert!(game_data.game_state == GameState::DONE);
+        assert!(!blindfold.hidden(&game_data));
+
+        let record = blindfold.to_record(&game_data);
+        assert_eq!(record.mode, GameMode::Blindfold);
+        assert_eq!(
+            record.time,
+            blindfold.memo_time().unwrap() + game_data.base_time
+        );
+        assert!(!record.dnf);
+
+        Ok(())
+    }
+
+    #[test]
+    fn blindfold_should_fail_unsolved_board() -> Result<(), Box<dyn Error>> {
+        let (mut blindfold, mut game_data) = blindfold(Duration::from_secs(0))?;
+
+        // Out of memo time, the clock starts without a move.
+        assert!(blindfold.check_memo(&mut game_data));
+        assert_eq!(blindfold.memo_time(), Some(Duration::from_secs(0)));
+        assert!(game_data.game_state == GameState::PLAYING);
+
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        finish_blindfold(&mut game_data);
+        assert!(game_data.game_state == GameState::UNSOLVED);
+        assert!(blindfold.to_record(&game_data).dnf);
+
+        Ok(())
+    }
+}
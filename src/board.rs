@@ -0,0 +1,585 @@
+//! Board representation, moves and solvability.
+
+use std::{error::Error, fmt, str::FromStr};
+
+/// A sliding puzzle position. Tiles are stored row by row and `0` is the blank.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    tiles: Vec<u16>,
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize, tiles: Vec<u16>) -> Result<Board, Box<dyn Error>> {
+        check_size(width, height)?;
+        if tiles.len() != width * height {
+            return Err(format!(
+                "Expected {} tiles for a {}x{} board, got {}",
+                width * height,
+                width,
+                height,
+                tiles.len()
+            )
+            .into());
+        }
+
+        let mut seen = vec![false; tiles.len()];
+        for tile in tiles.iter() {
+            let tile = *tile as usize;
+            if tile >= seen.len() || seen[tile] {
+                return Err(format!("Tiles must be a permutation of 0..{}", seen.len()).into());
+            }
+            seen[tile] = true;
+        }
+
+        Ok(Board {
+            width,
+            height,
+            tiles,
+        })
+    }
+
+    /// The solved board of `width` x `height`, which must be at least 2x2 like any board.
+    /// `Goal::board` returns the error instead of panicking on smaller sizes.
+    pub fn solved(width: usize, height: usize) -> Board {
+        if let Err(e) = check_size(width, height) {
+            panic!("{}", e);
+        }
+
+        let length = width * height;
+        let tiles = (0..length)
+            .map(|index| {
+                if index + 1 == length {
+                    0
+                } else {
+                    index as u16 + 1
+                }
+            })
+            .collect();
+
+        Board {
+            width,
+            height,
+            tiles,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn tiles(&self) -> &[u16] {
+        &self.tiles
+    }
+
+    pub fn blank_index(&self) -> usize {
+        self.tiles.iter().position(|x| *x == 0).unwrap()
+    }
+}
+
+impl fmt::Display for Board {
+    /// Rows are separated by `/` and tiles by `,`, e.g. `1,2,3/4,5,6/7,8,0`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (row_index, row) in self.tiles.chunks(self.width).enumerate() {
+            if row_index > 0 {
+                write!(f, "/")?;
+            }
+            for (index, tile) in row.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", tile)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Board {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Board, Box<dyn Error>> {
+        let rows = s
+            .trim()
+            .split('/')
+            .map(|row| {
+                row.split(',')
+                    .map(|tile| tile.trim().parse::<u16>())
+                    .collect::<Result<Vec<u16>, _>>()
+            })
+            .collect::<Result<Vec<Vec<u16>>, _>>()
+            .map_err(|e| format!("Invalid tile in board '{}': {}", s, e))?;
+
+        let width = rows[0].len();
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(format!("Rows of board '{}' have different lengths", s).into());
+        }
+
+        Board::new(width, rows.len(), rows.concat())
+    }
+}
+
+/// Parses a board size written as `WxH`, e.g. `4x4` or `3x5`.
+pub fn parse_size(s: &str) -> Result<(usize, usize), Box<dyn Error>> {
+    let mut parts = s.split(['x', 'X']);
+    let (width, height) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(width), Some(height), None) => (width.parse::<usize>()?, height.parse::<usize>()?),
+        _ => return Err(format!("Invalid size '{}', expected WxH", s).into()),
+    };
+
+    if let Err(e) = check_size(width, height) {
+        return Err(format!("Invalid size '{}': {}", s, e).into());
+    }
+
+    Ok((width, height))
+}
+
+/// Most cells of a board, tiles are numbered with `u16`.
+pub const MAX_CELLS: usize = u16::MAX as usize + 1;
+
+/// Smaller boards have no moves to make, the blank needs a neighbour in both directions. Bigger
+/// ones would run out of tile numbers.
+pub(crate) fn check_size(width: usize, height: usize) -> Result<(), Box<dyn Error>> {
+    if width < 2 || height < 2 {
+        return Err("Board must be at least 2x2!".into());
+    }
+    if width
+        .checked_mul(height)
+        .is_none_or(|cells| cells > MAX_CELLS)
+    {
+        return Err(format!("Board can have at most {} cells!", MAX_CELLS).into());
+    }
+
+    Ok(())
+}
+
+pub fn is_solvable(board: &Board) -> bool {
+    // odd width : solvable when inversion count is even
+    // even width : solvable : blank even row (count from bottom, count start from 1) and odd count inversions
+    // even width : solvable : blank odd row (count from bottom, count start from 1) and even count inversions
+
+    let inversion_count = count_inversion(&board.tiles);
+
+    if board.width % 2 == 1 {
+        return inversion_count.is_multiple_of(2);
+    }
+
+    let blank_row = board.height - board.blank_index() / board.width;
+
+    if blank_row.is_multiple_of(2) {
+        // blank row is even
+
+        inversion_count % 2 == 1
+    } else {
+        // blank row is odd
+
+        inversion_count.is_multiple_of(2)
+    }
+}
+
+/// Whether `board` can be slid into `goal`. Every move swaps the blank with a tile, so the
+/// permutation between the two boards must have the parity of the blank's distance between them.
+pub fn is_solvable_to(board: &Board, goal: &Board) -> bool {
+    if (board.width, board.height) != (goal.width, goal.height) {
+        return false;
+    }
+
+    let mut goal_cells = vec![0; goal.tiles.len()];
+    for (cell, tile) in goal.tiles.iter().enumerate() {
+        goal_cells[*tile as usize] = cell;
+    }
+
+    // Where each cell's tile goes, its cycles give the parity.
+    let targets: Vec<usize> = board
+        .tiles
+        .iter()
+        .map(|tile| goal_cells[*tile as usize])
+        .collect();
+    let mut visited = vec![false; targets.len()];
+    let mut cycles = 0;
+    for start in 0..targets.len() {
+        if visited[start] {
+            continue;
+        }
+
+        cycles += 1;
+        let mut cell = start;
+        while !visited[cell] {
+            visited[cell] = true;
+            cell = targets[cell];
+        }
+    }
+
+    let (blank, goal_blank) = (board.blank_index(), goal.blank_index());
+    let distance = (blank / board.width).abs_diff(goal_blank / board.width)
+        + (blank % board.width).abs_diff(goal_blank % board.width);
+
+    (targets.len() - cycles) % 2 == distance % 2
+}
+
+/// Number of pairs of tiles in the wrong order, the blank left out. They are counted while merge
+/// sorting the tiles, in O(n log n) so that big boards stay fast.
+pub fn count_inversion(arr: &[u16]) -> usize {
+    let mut tiles: Vec<u16> = arr.iter().copied().filter(|tile| *tile != 0).collect();
+    let mut buffer = vec![0; tiles.len()];
+
+    sort_counting_inversions(&mut tiles, &mut buffer)
+}
+
+/// Sorts `tiles` using `buffer`, of the same length, and returns the number of inversions.
+fn sort_counting_inversions(tiles: &mut [u16], buffer: &mut [u16]) -> usize {
+    if tiles.len() < 2 {
+        return 0;
+    }
+
+    let middle = tiles.len() / 2;
+    let mut count = sort_counting_inversions(&mut tiles[..middle], &mut buffer[..middle])
+        + sort_counting_inversions(&mut tiles[middle..], &mut buffer[middle..]);
+
+    let (mut left, mut right) = (0, middle);
+    for slot in buffer.iter_mut() {
+        if right == tiles.len() || (left < middle && tiles[left] <= tiles[right]) {
+            *slot = tiles[left];
+            left += 1;
+        } else {
+            // The tile passes every tile still waiting in the first half.
+            count += middle - left;
+            *slot = tiles[right];
+            right += 1;
+        }
+    }
+    tiles.copy_from_slice(buffer);
+
+    count
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    UP,
+    DOWN,
+    LEFT,
+    RIGHT,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 4] = [
+        Operation::UP,
+        Operation::DOWN,
+        Operation::LEFT,
+        Operation::RIGHT,
+    ];
+
+    /// Move keys double as the notation for move sequences: `w`, `a`, `s`, `d`.
+    pub fn from_char(char: char) -> Option<Operation> {
+        match char {
+            'w' => Some(Operation::UP),
+            's' => Some(Operation::DOWN),
+            'a' => Some(Operation::LEFT),
+            'd' => Some(Operation::RIGHT),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Operation::UP => 'w',
+            Operation::DOWN => 's',
+            Operation::LEFT => 'a',
+            Operation::RIGHT => 'd',
+        }
+    }
+
+    pub fn inverse(self) -> Operation {
+        match self {
+            Operation::UP => Operation::DOWN,
+            Operation::DOWN => Operation::UP,
+            Operation::LEFT => Operation::RIGHT,
+            Operation::RIGHT => Operation::LEFT,
+        }
+    }
+}
+
+pub fn parse_moves(s: &str) -> Result<Vec<Operation>, Box<dyn Error>> {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| Operation::from_char(c).ok_or_else(|| format!("Invalid move '{}'", c).into()))
+        .collect()
+}
+
+pub fn format_moves(moves: &[Operation]) -> String {
+    moves.iter().map(|operation| operation.to_char()).collect()
+}
+
+/// Returns the tile index swapped with the blank by `operation`, if the move is possible.
+pub fn target_index(board: &Board, operation: Operation) -> Option<usize> {
+    swap_index(board.width, board.height, board.blank_index(), operation)
+}
+
+/// Same as `target_index`, for callers that track the blank position themselves.
+pub(crate) fn swap_index(
+    width: usize,
+    height: usize,
+    index_blank: usize,
+    operation: Operation,
+) -> Option<usize> {
+    let (column, row) = (index_blank % width, index_blank / width);
+
+    match operation {
+        Operation::UP if row + 1 < height => Some(index_blank + width),
+        Operation::DOWN if row > 0 => Some(index_blank - width),
+        Operation::LEFT if column + 1 < width => Some(index_blank + 1),
+        Operation::RIGHT if column > 0 => Some(index_blank - 1),
+        _ => None,
+    }
+}
+
+pub fn move_tile(board: &Board, operation: Operation) -> Board {
+    let mut next_board = board.clone();
+
+    if let Some(index_to_swap) = target_index(board, operation) {
+        next_board.tiles.swap(board.blank_index(), index_to_swap);
+    }
+
+    next_board
+}
+
+pub fn is_done(board: &Board) -> bool {
+    *board == Board::solved(board.width, board.height)
+}
+
+/// Sum of the distances of every tile to its solved position, 0 only for a solved board.
+pub fn manhattan_distance(board: &Board) -> usize {
+    board
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile != 0)
+        .map(|(index, tile)| {
+            let goal = *tile as usize - 1;
+
+            (index % board.width).abs_diff(goal % board.width)
+                + (index / board.width).abs_diff(goal / board.width)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble::shuffle_arr;
+    use rand::{seq::SliceRandom, Rng};
+
+    #[test]
+    fn count_inversion_should_correct() {
+        {
+            let arr = [2, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0];
+            let count = count_inversion(&arr);
+            assert_eq!(count, 1);
+        }
+
+        {
+            let arr = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+            let count = count_inversion(&arr);
+            assert_eq!(count, 0);
+        }
+
+        {
+            let arr = [13, 2, 10, 3, 1, 12, 8, 4, 5, 0, 9, 6, 15, 14, 11, 7];
+            let count = count_inversion(&arr);
+            assert_eq!(count, 41);
+        }
+
+        {
+            let arr = [6, 13, 7, 10, 8, 9, 11, 0, 15, 2, 12, 5, 14, 3, 1, 4];
+            let count = count_inversion(&arr);
+            assert_eq!(count, 62);
+        }
+
+        {
+            let arr = [3, 9, 1, 15, 14, 11, 4, 6, 13, 0, 10, 12, 2, 7, 8, 5];
+            let count = count_inversion(&arr);
+            assert_eq!(count, 56);
+        }
+    }
+
+    #[test]
+    fn count_inversion_should_match_pair_count_on_big_boards() {
+        let mut rng = rand::thread_rng();
+
+        for &cells in [2, 3, 100, 1000].iter() {
+            let mut arr: Vec<u16> = (0..cells).collect();
+            arr.shuffle(&mut rng);
+
+            let pairs = (0..arr.len())
+                .flat_map(|i| (i + 1..arr.len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| arr[i] != 0 && arr[j] != 0 && arr[i] > arr[j])
+                .count();
+            assert_eq!(count_inversion(&arr), pairs);
+        }
+    }
+
+    #[test]
+    fn is_solvable_should_correct() -> Result<(), Box<dyn Error>> {
+        {
+            let arr = [13, 2, 10, 3, 1, 12, 8, 4, 5, 0, 9, 6, 15, 14, 11, 7];
+            let is_solvable = is_solvable(&Board::new(4, 4, arr.to_vec())?);
+            assert!(is_solvable);
+        }
+
+        {
+            let arr = [6, 13, 7, 10, 8, 9, 11, 0, 15, 2, 12, 5, 14, 3, 1, 4];
+            let is_solvable = is_solvable(&Board::new(4, 4, arr.to_vec())?);
+            assert!(is_solvable);
+        }
+
+        {
+            let arr = [3, 9, 1, 15, 14, 11, 4, 6, 13, 0, 10, 12, 2, 7, 8, 5];
+            let is_solvable = is_solvable(&Board::new(4, 4, arr.to_vec())?);
+            assert!(!is_solvable);
+        }
+
+        {
+            let test_set = [
+                [13, 10, 11, 6, 5, 3, 1, 4, 8, 0, 12, 2, 14, 7, 9, 15],
+                [9, 2, 15, 13, 7, 4, 12, 6, 8, 1, 0, 14, 5, 10, 3, 11],
+                [4, 8, 7, 12, 5, 0, 13, 15, 9, 1, 6, 3, 11, 14, 10, 2],
+                [7, 10, 11, 1, 0, 9, 3, 4, 5, 8, 13, 2, 14, 6, 12, 15],
+                [14, 11, 8, 15, 12, 5, 13, 3, 6, 2, 9, 0, 1, 7, 10, 4],
+                [4, 12, 15, 9, 2, 13, 14, 3, 5, 7, 8, 6, 11, 1, 10, 0],
+                [12, 14, 2, 11, 1, 7, 0, 10, 6, 5, 13, 4, 8, 9, 15, 3],
+                [4, 13, 8, 7, 10, 6, 2, 9, 5, 0, 14, 11, 12, 15, 1, 3],
+                [4, 5, 11, 13, 3, 7, 8, 12, 0, 14, 2, 6, 10, 15, 1, 9],
+                [15, 4, 3, 6, 2, 7, 5, 1, 8, 11, 0, 14, 13, 9, 10, 12],
+                [7, 10, 13, 5, 6, 8, 11, 0, 1, 2, 12, 14, 3, 4, 15, 9],
+                [10, 9, 0, 11, 1, 6, 15, 7, 4, 5, 2, 12, 14, 13, 3, 8],
+                [4, 15, 14, 8, 10, 9, 3, 12, 7, 6, 13, 0, 2, 11, 1, 5],
+                [9, 7, 15, 12, 8, 6, 13, 5, 14, 2, 11, 1, 4, 0, 10, 3],
+                [3, 5, 14, 4, 0, 10, 12, 7, 15, 9, 6, 11, 2, 1, 13, 8],
+                [5, 15, 3, 10, 9, 8, 7, 14, 4, 13, 12, 2, 0, 1, 6, 11],
+                [5, 8, 7, 2, 14, 15, 12, 10, 0, 6, 9, 1, 4, 11, 13, 3],
+                [3, 6, 15, 14, 7, 9, 11, 10, 2, 1, 13, 5, 0, 12, 8, 4],
+                [4, 8, 13, 1, 11, 7, 12, 10, 2, 3, 0, 14, 6, 5, 9, 15],
+                [3, 12, 0, 11, 10, 5, 7, 14, 6, 13, 2, 15, 8, 9, 4, 1],
+            ];
+            for test in test_set.iter() {
+                let is_solvable = is_solvable(&Board::new(4, 4, test.to_vec())?);
+                assert!(is_solvable);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn move_tile_should_generate_correct_arr() -> Result<(), Box<dyn Error>> {
+        let mut rng = rand::thread_rng();
+        let mut arr = shuffle_arr(&mut rng, 4, 4);
+
+        for _ in 0..10_000 {
+            assert!(is_solvable(&arr));
+
+            let random_number = rng.gen_range(0, 4);
+            let operation = match random_number {
+                0 => Operation::UP,
+                1 => Operation::DOWN,
+                2 => Operation::LEFT,
+                3 => Operation::RIGHT,
+                _ => Operation::UP,
+            };
+
+            arr = move_tile(&arr, operation);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_should_parse_and_display() -> Result<(), Box<dyn Error>> {
+        let board: Board = "1,2,3/4,5,6/7,8,0".parse()?;
+        assert_eq!(board, Board::solved(3, 3));
+        assert_eq!(board.to_string(), "1,2,3/4,5,6/7,8,0");
+
+        let board: Board = "2,1,3,0/4,5,6,7".parse()?;
+        assert_eq!((board.width(), board.height()), (4, 2));
+
+        assert!("1,2/3".parse::<Board>().is_err());
+        assert!("1,1/2,0".parse::<Board>().is_err());
+        assert!("1,2/3,4".parse::<Board>().is_err());
+
+        // Tiles are numbered with u16, a 256x256 board uses every number.
+        assert_eq!(parse_size("256x256")?, (256, 256));
+        assert_eq!(Board::solved(256, 256).tiles()[MAX_CELLS - 2], u16::MAX);
+        assert!(parse_size("300x300").is_err());
+        assert!(parse_size("1x2").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2x2")]
+    fn solved_should_refuse_boards_without_moves() {
+        Board::solved(1, 1);
+    }
+
+    #[test]
+    fn is_solvable_should_handle_other_sizes() -> Result<(), Box<dyn Error>> {
+        assert!(is_solvable(&"1,2,3/4,5,6/7,8,0".parse()?));
+        assert!(!is_solvable(&"2,1,3/4,5,6/7,8,0".parse()?));
+        assert!(is_solvable(&"8,6,7/2,5,4/3,0,1".parse()?));
+        assert!(is_solvable(&"1,2,3,4/5,6,0,7".parse()?));
+        assert!(!is_solvable(&"1,2,3,4/5,7,6,0".parse()?));
+
+        let mut rng = rand::thread_rng();
+        for (width, height) in [(3, 3), (2, 4), (5, 3), (6, 6)].iter() {
+            let board = shuffle_arr(&mut rng, *width, *height);
+            assert!(is_solvable(&board));
+            assert!(!is_done(&board));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_solvable_to_should_agree_with_is_solvable() -> Result<(), Box<dyn Error>> {
+        let mut rng = rand::thread_rng();
+
+        for &(width, height) in [(2, 2), (3, 3), (4, 4), (3, 4), (5, 2)].iter() {
+            let solved = Board::solved(width, height);
+            let mut tiles: Vec<u16> = (0..(width * height) as u16).collect();
+
+            for _ in 0..50 {
+                tiles.shuffle(&mut rng);
+                let board = Board::new(width, height, tiles.clone())?;
+                assert_eq!(is_solvable_to(&board, &solved), is_solvable(&board));
+            }
+        }
+
+        // The blank can go first on 3x3, on 2x2 it takes tiles trading places.
+        assert!(is_solvable_to(
+            &Board::solved(3, 3),
+            &"0,1,2/3,4,5/6,7,8".parse()?
+        ));
+        assert!(!is_solvable_to(&Board::solved(2, 2), &"0,1/2,3".parse()?));
+        assert!(!is_solvable_to(&"2,1/3,0".parse()?, &Board::solved(2, 2)));
+        assert!(!is_solvable_to(&Board::solved(2, 2), &Board::solved(3, 3)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn manhattan_distance_should_sum_tile_distances() -> Result<(), Box<dyn Error>> {
+        assert_eq!(manhattan_distance(&Board::solved(4, 4)), 0);
+        assert_eq!(manhattan_distance(&"1,2,3/4,5,6/0,7,8".parse()?), 2);
+        assert_eq!(manhattan_distance(&"8,6,7/2,5,4/3,0,1".parse()?), 21);
+        assert_eq!(manhattan_distance(&"1,2,3,4/5,6,0,7".parse()?), 1);
+
+        Ok(())
+    }
+}
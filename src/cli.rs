@@ -0,0 +1,908 @@
+use crate::batch::run_batch;
+use crate::play::{play, Network, PlayOptions};
+use crate::watch::watch;
+use clap::{Parser, Subcommand};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rust_15_puzzle_cli::{
+    daily,
+    drill::Drill,
+    format_moves,
+    game::GameMode,
+    goal::Goal,
+    heuristic::Heuristic,
+    is_solvable_to, method, move_tile, parse_moves, parse_size, random_walk,
+    reduction::solve_reduction,
+    scramble_board, shuffle_arr, solve_to, solve_with,
+    stats::{load_records, SolveRecord},
+    table::{self, table_path, DistanceTable},
+    target_index, timing, Board, Difficulty,
+};
+use serde_json::json;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The command succeeded: the board was solved, generated or verified as solved.
+pub const EXIT_SUCCESS: i32 = 0;
+/// The command ran but the answer is negative: an unsolvable board or moves that don't solve it.
+pub const EXIT_FAILURE: i32 = 1;
+/// The arguments could not be parsed, same code clap uses for usage errors.
+pub const EXIT_INVALID_INPUT: i32 = 2;
+/// The command failed while running: the terminal, a file or the network.
+pub const EXIT_ERROR: i32 = 3;
+
+/// Arguments found invalid once the command runs, like a goal of another size than the board.
+/// They exit with `EXIT_INVALID_INPUT` like the ones clap turns away, other errors with
+/// `EXIT_ERROR`.
+#[derive(Debug)]
+pub struct InvalidInput(String);
+
+impl fmt::Display for InvalidInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for InvalidInput {}
+
+pub fn invalid_input(e: Box<dyn Error>) -> Box<dyn Error> {
+    Box::new(InvalidInput(e.to_string()))
+}
+
+/// rust-15-puzzle-cli is 15puzzle terminal game written in Rust!
+///
+/// Boards are written row by row, rows separated by `/` and tiles by `,` with `0` as the
+/// blank, e.g. `1,2,3/4,5,6/7,0,8`. Moves are written with the move keys `w`, `a`, `s`, `d`.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play the puzzle in the terminal (default)
+    Play {
+        /// Board size as WxH
+        #[arg(long, default_value = "4x4", value_parser = size_arg)]
+        size: (usize, usize),
+        /// Scramble difficulty: easy, medium, hard or expert
+        #[arg(long, default_value = "expert", value_parser = difficulty_arg)]
+        difficulty: Difficulty,
+        /// Play today's daily puzzle, the same scramble for everybody on the same date
+        #[arg(long, conflicts_with = "race")]
+        daily: bool,
+        /// Two players race side by side, player 1 on w,a,s,d and player 2 on arrows
+        #[arg(long)]
+        race: bool,
+        /// Host a race over the network, waiting for an opponent on ADDR (port 7878 by default)
+        #[arg(
+            long,
+            value_name = "ADDR",
+            num_args = 0..=1,
+            default_missing_value = "0.0.0.0",
+            conflicts_with_all = ["daily", "race", "join"]
+        )]
+        host: Option<String>,
+        /// Join a race hosted on ADDR, the host picks the size and difficulty
+        #[arg(long, value_name = "ADDR", conflicts_with_all = ["daily", "race"])]
+        join: Option<String>,
+        /// Stream the game to `watch` on ADDR (127.0.0.1:7878 by default)
+        #[arg(
+            long,
+            value_name = "ADDR",
+            num_args = 0..=1,
+            default_missing_value = "127.0.0.1",
+            conflicts_with_all = ["race", "host", "join"]
+        )]
+        spectate: Option<String>,
+        /// Race a dimmed replay of your best solve of the scramble, or of the size
+        #[arg(long, conflicts_with_all = ["race", "host", "join"])]
+        ghost: bool,
+        /// Solve N puzzles in a row on one clock, recorded as a single result
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u64).range(1..),
+            conflicts_with_all = ["daily", "race", "host", "join", "ghost"]
+        )]
+        marathon: Option<u64>,
+        /// Solve as many puzzles as possible in SECONDS, high scores are kept per size
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            value_parser = clap::value_parser!(u64).range(1..),
+            conflicts_with_all = ["daily", "race", "host", "join", "ghost", "marathon"]
+        )]
+        time_attack: Option<u64>,
+        /// Solve within the optimal solution length plus EXTRA moves (0 by default)
+        #[arg(
+            long,
+            value_name = "EXTRA",
+            num_args = 0..=1,
+            default_missing_value = "0",
+            conflicts_with_all = ["daily", "race", "host", "join", "marathon", "time_attack"]
+        )]
+        challenge: Option<usize>,
+        /// Memorize the scramble for up to SECONDS (60 by default), then solve it with the tiles
+        /// hidden
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            num_args = 0..=1,
+            default_missing_value = "60",
+            value_parser = clap::value_parser!(u64).range(1..),
+            conflicts_with_all = [
+                "daily", "race", "host", "join", "ghost", "marathon", "time_attack", "challenge"
+            ]
+        )]
+        blindfold: Option<u64>,
+        /// Inspect the scramble for SECONDS (15 by default) before the clock starts, a late start
+        /// is a +2 and more than 2s late a DNF
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            num_args = 0..=1,
+            default_missing_value = "15",
+            value_parser = clap::value_parser!(u64).range(1..),
+            conflicts_with_all = ["race", "host", "join", "marathon", "time_attack", "blindfold"]
+        )]
+        inspection: Option<u64>,
+        /// Learn the row-by-row method step by step, practice games aren't recorded
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "daily", "race", "host", "join", "ghost", "marathon", "time_attack", "challenge",
+                "blindfold", "inspection"
+            ]
+        )]
+        tutorial: bool,
+        /// Practice part of the board: last-block (the last 2x3 block), row-pair (the last two
+        /// tiles of the first row) or column-pair (the last two tiles of the first column)
+        #[arg(
+            long,
+            value_name = "DRILL",
+            value_parser = drill_arg,
+            conflicts_with_all = [
+                "daily", "race", "host", "join", "ghost", "marathon", "time_attack", "challenge",
+                "blindfold", "tutorial"
+            ]
+        )]
+        drill: Option<Drill>,
+        /// Solve into another layout: blank-first, column-major, snake, spiral or any board
+        /// like 0,1,2/3,4,5. Games with another goal aren't recorded
+        #[arg(
+            long,
+            value_parser = goal_arg,
+            conflicts_with_all = [
+                "daily", "host", "join", "spectate", "ghost", "marathon", "time_attack",
+                "tutorial", "drill"
+            ]
+        )]
+        goal: Option<Goal>,
+    },
+    /// Find an optimal solution for a board
+    Solve {
+        #[arg(value_parser = board_arg)]
+        board: Board,
+        /// Solve row by row like a human instead, fast on boards of any size but not optimal.
+        /// The moves are also listed by phase
+        #[arg(long)]
+        reduction: bool,
+        /// Lower bound guiding the search: manhattan, linear-conflict, walking-distance or
+        /// pattern-database
+        #[arg(
+            long,
+            default_value = "linear-conflict",
+            value_parser = heuristic_arg,
+            conflicts_with = "reduction"
+        )]
+        heuristic: Heuristic,
+        /// Layout to solve into: standard, blank-first, column-major, snake, spiral or a board
+        #[arg(
+            long,
+            default_value = "standard",
+            value_parser = goal_arg,
+            conflicts_with = "reduction"
+        )]
+        goal: Goal,
+    },
+    /// Generate random solvable boards, one JSON object per line
+    Generate {
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// Board size as WxH
+        #[arg(long, default_value = "4x4", value_parser = size_arg)]
+        size: (usize, usize),
+        /// Scramble difficulty: easy, medium, hard or expert
+        #[arg(long, default_value = "expert", value_parser = difficulty_arg)]
+        difficulty: Difficulty,
+    },
+    /// Apply moves to a board and check whether they solve it
+    Verify {
+        #[arg(value_parser = board_arg)]
+        board: Board,
+        moves: String,
+        /// Layout the moves should end in
+        #[arg(long, default_value = "standard", value_parser = goal_arg)]
+        goal: Goal,
+    },
+    /// Solve boards read one per line, writing one JSON object per board
+    Batch {
+        /// File with one board per line, stdin when omitted
+        input: Option<PathBuf>,
+        /// Append results to this file, skipping boards it already contains
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Number of solver threads, one per CPU by default
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+    /// Summarize the recorded games
+    Stats,
+    /// Compute the optimal distance of every position of a small board, saved for hints
+    Table {
+        /// Board size as WxH, at most 9 cells
+        #[arg(long, default_value = "3x3", value_parser = size_arg)]
+        size: (usize, usize),
+    },
+    /// Compare the solver's heuristics on a fixed set of scrambles, one JSON object per heuristic
+    Bench {
+        /// Board size as WxH
+        #[arg(long, default_value = "3x3", value_parser = size_arg)]
+        size: (usize, usize),
+        /// Number of scrambles
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+        /// Seed of the scrambles, the same seed always gives the same set
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Scramble with random walks of DEPTH moves instead of uniformly random positions, to
+        /// keep bigger boards within reach of the weaker heuristics
+        #[arg(long)]
+        depth: Option<usize>,
+        /// Heuristic to run, can be repeated, all of them when omitted
+        #[arg(long = "heuristic", value_parser = heuristic_arg)]
+        heuristics: Vec<Heuristic>,
+    },
+    /// Follow a game streamed with `play --spectate`, read-only
+    Watch {
+        /// Address of the game, port 7878 when omitted
+        #[arg(default_value = "localhost")]
+        address: String,
+    },
+}
+
+fn board_arg(s: &str) -> Result<Board, String> {
+    s.parse().map_err(|e: Box<dyn Error>| e.to_string())
+}
+
+fn size_arg(s: &str) -> Result<(usize, usize), String> {
+    parse_size(s).map_err(|e| e.to_string())
+}
+
+fn goal_arg(s: &str) -> Result<Goal, String> {
+    s.parse().map_err(|e: Box<dyn Error>| e.to_string())
+}
+
+fn drill_arg(s: &str) -> Result<Drill, String> {
+    s.parse().map_err(|e: Box<dyn Error>| e.to_string())
+}
+
+fn heuristic_arg(s: &str) -> Result<Heuristic, String> {
+    s.parse().map_err(|e: Box<dyn Error>| e.to_string())
+}
+
+fn difficulty_arg(s: &str) -> Result<Difficulty, String> {
+    s.parse().map_err(|e: Box<dyn Error>| e.to_string())
+}
+
+impl Cli {
+    /// Runs the selected command and returns the process exit code.
+    pub fn run(self) -> Result<i32, Box<dyn Error>> {
+        // Without a command the game starts, with the same defaults as a bare `play`.
+        let command = match self.command {
+            Some(command) => command,
+            None => Cli::try_parse_from([env!("CARGO_PKG_NAME"), "play"])?
+                .command
+                .ok_or("Missing default command")?,
+        };
+
+        match command {
+            Command::Play {
+                size,
+                difficulty,
+                daily,
+                race,
+                host,
+                join,
+                spectate,
+                ghost,
+                marathon,
+                time_attack,
+                challenge,
+                blindfold,
+                inspection,
+                tutorial,
+                drill,
+                goal,
+            } => {
+                let network = host.map(Network::Host).or(join.map(Network::Join));
+
+                play(PlayOptions {
+                    width: size.0,
+                    height: size.1,
+                    difficulty,
+                    daily,
+                    race,
+                    network,
+                    spectate,
+                    ghost,
+                    marathon: marathon.map(|puzzles| puzzles as usize),
+                    time_attack: time_attack.map(Duration::from_secs),
+                    challenge,
+                    blindfold: blindfold.map(Duration::from_secs),
+                    inspection: inspection.map(Duration::from_secs),
+                    tutorial,
+                    drill,
+                    goal: goal.unwrap_or(Goal::Standard),
+                })?;
+                Ok(EXIT_SUCCESS)
+            }
+            Command::Solve {
+                board,
+                reduction,
+                heuristic,
+                goal,
+            } => run_solve(&board, reduction, heuristic, &goal),
+            Command::Generate {
+                count,
+                size,
+                difficulty,
+            } => run_generate(count, size, difficulty),
+            Command::Verify { board, moves, goal } => run_verify(&board, &moves, &goal),
+            Command::Batch {
+                input,
+                output,
+                threads,
+            } => {
+                let threads = threads.unwrap_or_else(|| {
+                    thread::available_parallelism()
+                        .map(|threads| threads.get())
+                        .unwrap_or(1)
+                });
+
+                run_batch(input.as_deref(), output.as_deref(), threads)
+            }
+            Command::Stats => run_stats(),
+            Command::Table { size } => run_table(size),
+            Command::Bench {
+                size,
+                count,
+                seed,
+                depth,
+                heuristics,
+            } => run_bench(size, count, seed, depth, &heuristics),
+            Command::Watch { address } => {
+                watch(&address)?;
+                Ok(EXIT_SUCCESS)
+            }
+        }
+    }
+}
+
+fn run_solve(
+    board: &Board,
+    reduction: bool,
+    heuristic: Heuristic,
+    goal: &Goal,
+) -> Result<i32, Box<dyn Error>> {
+    let goal = goal
+        .board(board.width(), board.height())
+        .map_err(invalid_input)?;
+    heuristic
+        .check_size(board.width(), board.height())
+        .map_err(invalid_input)?;
+    if !is_solvable_to(board, &goal) {
+        println!(
+            "{}",
+            json!({ "board": board.to_string(), "solvable": false })
+        );
+        return Ok(EXIT_FAILURE);
+    }
+
+    let start_time = Instant::now();
+
+    if reduction {
+        let solution = solve_reduction(board)?;
+        let moves = solution.moves();
+        let phases: Vec<_> = solution
+            .phases
+            .iter()
+            .map(|phase| {
+                json!({
+                    "phase": phase.name,
+                    "moves": format_moves(&phase.moves),
+                    "length": phase.moves.len(),
+                })
+            })
+            .collect();
+
+        println!(
+            "{}",
+            json!({
+                "board": board.to_string(),
+                "solvable": true,
+                "moves": format_moves(&moves),
+                "length": moves.len(),
+                "phases": phases,
+                "time_ms": start_time.elapsed().as_millis() as u64,
+            })
+        );
+
+        return Ok(EXIT_SUCCESS);
+    }
+
+    let solution = solve_to(board, &goal, heuristic)?;
+
+    println!(
+        "{}",
+        json!({
+            "board": board.to_string(),
+            "solvable": true,
+            "moves": format_moves(&solution.moves),
+            "length": solution.moves.len(),
+            "nodes_expanded": solution.nodes_expanded,
+            "time_ms": start_time.elapsed().as_millis() as u64,
+        })
+    );
+
+    Ok(EXIT_SUCCESS)
+}
+
+fn run_generate(
+    count: usize,
+    size: (usize, usize),
+    difficulty: Difficulty,
+) -> Result<i32, Box<dyn Error>> {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..count {
+        let scramble = scramble_board(&mut rng, size.0, size.1, difficulty);
+        println!(
+            "{}",
+            json!({
+                "board": scramble.board.to_string(),
+                "width": size.0,
+                "height": size.1,
+                "difficulty": difficulty.name(),
+                "optimal_length": scramble.optimal_length,
+            })
+        );
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+fn run_verify(board: &Board, moves: &str, goal: &Goal) -> Result<i32, Box<dyn Error>> {
+    let goal = goal
+        .board(board.width(), board.height())
+        .map_err(invalid_input)?;
+    let moves = parse_moves(moves).map_err(invalid_input)?;
+    let mut current = board.clone();
+
+    for (index, operation) in moves.iter().enumerate() {
+        // Moves that can't be made don't solve the board, the answer is no rather than an error.
+        if target_index(&current, *operation).is_none() {
+            println!(
+                "{}",
+                json!({
+                    "board": board.to_string(),
+                    "moves": moves.len(),
+                    "final": current.to_string(),
+                    "solved": false,
+                    "error_at": index + 1,
+                })
+            );
+            return Ok(EXIT_FAILURE);
+        }
+
+        current = move_tile(&current, *operation);
+    }
+
+    let solved = current == goal;
+    println!(
+        "{}",
+        json!({
+            "board": board.to_string(),
+            "moves": moves.len(),
+            "final": current.to_string(),
+            "solved": solved,
+        })
+    );
+
+    Ok(if solved { EXIT_SUCCESS } else { EXIT_FAILURE })
+}
+
+fn run_table(size: (usize, usize)) -> Result<i32, Box<dyn Error>> {
+    table::check_size(size.0, size.1).map_err(invalid_input)?;
+    let start_time = Instant::now();
+    let table = DistanceTable::load_or_build(size.0, size.1)?;
+    let distribution = table.distribution();
+
+    println!(
+        "{}",
+        json!({
+            "size": format!("{}x{}", size.0, size.1),
+            "positions": distribution.iter().sum::<usize>(),
+            "gods_number": distribution.len() - 1,
+            "distribution": distribution,
+            "path": table_path(size.0, size.1),
+            "time_ms": start_time.elapsed().as_millis() as u64,
+        })
+    );
+
+    Ok(EXIT_SUCCESS)
+}
+
+fn run_bench(
+    size: (usize, usize),
+    count: usize,
+    seed: u64,
+    depth: Option<usize>,
+    heuristics: &[Heuristic],
+) -> Result<i32, Box<dyn Error>> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let boards: Vec<Board> = (0..count)
+        .map(|_| match depth {
+            Some(depth) => random_walk(&mut rng, size.0, size.1, depth),
+            None => shuffle_arr(&mut rng, size.0, size.1),
+        })
+        .collect();
+    let heuristics = if heuristics.is_empty() {
+        &Heuristic::ALL[..]
+    } else {
+        heuristics
+    };
+    for heuristic in heuristics.iter() {
+        heuristic
+            .check_size(size.0, size.1)
+            .map_err(invalid_input)?;
+    }
+    let mut lengths: Option<Vec<usize>> = None;
+
+    for heuristic in heuristics.iter() {
+        // Solving the solved board builds the heuristic's tables, if any.
+        let start_time = Instant::now();
+        solve_with(&Board::solved(size.0, size.1), *heuristic)?;
+        let setup_time = start_time.elapsed();
+
+        let start_time = Instant::now();
+        let mut nodes_expanded = 0;
+        let mut solved_lengths = Vec::new();
+        for board in boards.iter() {
+            let solution = solve_with(board, *heuristic)?;
+            nodes_expanded += solution.nodes_expanded;
+            solved_lengths.push(solution.moves.len());
+        }
+        let time = start_time.elapsed();
+
+        if lengths
+            .as_ref()
+            .is_some_and(|lengths| *lengths != solved_lengths)
+        {
+            return Err(format!("{} found solutions of other lengths", heuristic).into());
+        }
+
+        println!(
+            "{}",
+            json!({
+                "heuristic": heuristic.name(),
+                "size": format!("{}x{}", size.0, size.1),
+                "boards": boards.len(),
+                "mean_length": solved_lengths.iter().sum::<usize>() as f64
+                    / boards.len().max(1) as f64,
+                "nodes_expanded": nodes_expanded,
+                "setup_ms": setup_time.as_millis() as u64,
+                "time_ms": time.as_millis() as u64,
+            })
+        );
+        lengths = Some(solved_lengths);
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+fn run_stats() -> Result<i32, Box<dyn Error>> {
+    let all_records = load_records()?;
+    let records: Vec<&SolveRecord> = all_records.iter().filter(|record| !record.dnf).collect();
+
+    let mut by_size: BTreeMap<(usize, usize), Vec<&SolveRecord>> = BTreeMap::new();
+    let mut marathons: BTreeMap<((usize, usize), usize), Vec<&SolveRecord>> = BTreeMap::new();
+    let mut time_attacks: BTreeMap<((usize, usize), u64), Vec<&SolveRecord>> = BTreeMap::new();
+    // Failed challenges are DNF, so they are counted from all records.
+    let mut challenges: BTreeMap<((usize, usize), usize), Vec<&SolveRecord>> = BTreeMap::new();
+    let mut blindfolds: BTreeMap<(usize, usize), Vec<&SolveRecord>> = BTreeMap::new();
+    for record in all_records.iter() {
+        match record.mode {
+            GameMode::Challenge(extra) => challenges
+                .entry((record.size(), extra))
+                .or_default()
+                .push(record),
+            GameMode::Blindfold => blindfolds.entry(record.size()).or_default().push(record),
+            _ => {}
+        }
+    }
+    for record in records.iter() {
+        match record.mode {
+            GameMode::Marathon(puzzles) => marathons
+                .entry((record.size(), puzzles))
+                .or_default()
+                .push(*record),
+            GameMode::TimeAttack(budget) => time_attacks
+                .entry((record.size(), budget.as_secs()))
+                .or_default()
+                .push(*record),
+            // Blindfold times include memorizing, drills solve part of the board, they are
+            // summarized on their own.
+            GameMode::Blindfold | GameMode::Drill(_) => {}
+            _ => by_size.entry(record.size()).or_default().push(*record),
+        }
+    }
+
+    let sizes: Vec<_> = by_size
+        .iter()
+        .map(|((width, height), records)| {
+            let count = records.len() as u128;
+            let times = records.iter().map(|record| record.time.as_millis());
+            let moves = records.iter().map(|record| record.moves.len());
+
+            json!({
+                "size": format!("{}x{}", width, height),
+                "solves": records.len(),
+                "best_time_ms": times.clone().min().unwrap_or(0) as u64,
+                "mean_time_ms": (times.sum::<u128>() / count) as u64,
+                "best_moves": moves.clone().min().unwrap_or(0),
+                "mean_moves": moves.sum::<usize>() as f64 / count as f64,
+                "paused_solves": records.iter().filter(|record| record.pauses > 0).count(),
+                "phases": phases(*width, *height, records),
+            })
+        })
+        .collect();
+
+    let marathons: Vec<_> = marathons
+        .iter()
+        .map(|(((width, height), puzzles), records)| {
+            let times = records.iter().map(|record| record.time.as_millis());
+
+            json!({
+                "size": format!("{}x{}", width, height),
+                "puzzles": puzzles,
+                "runs": records.len(),
+                "best_time_ms": times.clone().min().unwrap_or(0) as u64,
+                "mean_time_ms": (times.sum::<u128>() / records.len() as u128) as u64,
+            })
+        })
+        .collect();
+
+    let time_attacks: Vec<_> = time_attacks
+        .iter()
+        .map(|(((width, height), seconds), records)| {
+            json!({
+                "size": format!("{}x{}", width, height),
+                "seconds": seconds,
+                "runs": records.len(),
+                "high_score": records.iter().map(|record| record.splits.len()).max().unwrap_or(0),
+            })
+        })
+        .collect();
+
+    let challenges: Vec<_> = challenges
+        .iter()
+        .map(|(((width, height), extra), records)| {
+            json!({
+                "size": format!("{}x{}", width, height),
+                "extra_moves": extra,
+                "attempts": records.len(),
+                "passed": records.iter().filter(|record| !record.dnf).count(),
+            })
+        })
+        .collect();
+
+    let blindfolds: Vec<_> = blindfolds
+        .iter()
+        .map(|((width, height), records)| {
+            let solved: Vec<&&SolveRecord> = records.iter().filter(|record| !record.dnf).collect();
+            let memo_times = solved
+                .iter()
+                .map(|record| record.memo_time.unwrap_or_default().as_millis());
+
+            json!({
+                "size": format!("{}x{}", width, height),
+                "attempts": records.len(),
+                "solved": solved.len(),
+                "best_time_ms": solved.iter().map(|record| record.time.as_millis()).min().unwrap_or(0) as u64,
+                "mean_memo_ms": (memo_times.sum::<u128>() / solved.len().max(1) as u128) as u64,
+            })
+        })
+        .collect();
+
+    let today = daily::today();
+    let attempted_today: Vec<String> = all_records
+        .iter()
+        .filter(|record| record.mode == GameMode::Daily(today))
+        .map(|record| format!("{}x{}", record.scramble.width(), record.scramble.height()))
+        .collect();
+    let daily_solves = records
+        .iter()
+        .filter(|record| matches!(record.mode, GameMode::Daily(_)))
+        .count();
+
+    println!(
+        "{}",
+        json!({
+            "solves": records.len(),
+            "sizes": sizes,
+            "marathons": marathons,
+            "time_attacks": time_attacks,
+            "challenges": challenges,
+            "blindfolds": blindfolds,
+            "drills": drill_stats(&records),
+            "daily": {
+                "solves": daily_solves,
+                "streak": daily::streak(&all_records, today),
+                "attempted_today": attempted_today,
+            },
+        })
+    );
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// Solves of each drill by board size.
+fn drill_stats(records: &[&SolveRecord]) -> Vec<serde_json::Value> {
+    let mut drills: BTreeMap<((usize, usize), &str), Vec<&SolveRecord>> = BTreeMap::new();
+    for record in records.iter() {
+        if let GameMode::Drill(drill) = record.mode {
+            drills
+                .entry((record.size(), drill.name()))
+                .or_default()
+                .push(*record);
+        }
+    }
+
+    drills
+        .iter()
+        .map(|(((width, height), drill), records)| {
+            let count = records.len() as u128;
+            let times = records.iter().map(|record| record.time.as_millis());
+            let moves = records.iter().map(|record| record.moves.len());
+
+            json!({
+                "size": format!("{}x{}", width, height),
+                "drill": drill,
+                "solves": records.len(),
+                "best_time_ms": times.clone().min().unwrap_or(0) as u64,
+                "mean_time_ms": (times.sum::<u128>() / count) as u64,
+                "best_moves": moves.clone().min().unwrap_or(0),
+                "mean_moves": moves.sum::<usize>() as f64 / count as f64,
+            })
+        })
+        .collect()
+}
+
+/// Mean time spent on each stage of the row-by-row method, over the solves that recorded the
+/// time of every move.
+fn phases(width: usize, height: usize, records: &[&SolveRecord]) -> serde_json::Value {
+    let stages = method::stages(width, height);
+    let mut totals = vec![Duration::from_secs(0); stages.len()];
+    let mut timed = 0;
+
+    for record in records
+        .iter()
+        .filter(|record| !record.moves.is_empty() && record.move_times.len() == record.moves.len())
+    {
+        let times = timing::stage_times(&record.scramble, &record.moves, &record.move_times);
+        for (total, time) in totals.iter_mut().zip(times) {
+            *total += time;
+        }
+        timed += 1;
+    }
+
+    let total: Duration = totals.iter().sum();
+    let stage_stats: Vec<_> = stages
+        .iter()
+        .zip(totals.iter())
+        .map(|(stage, time)| {
+            json!({
+                "stage": stage.name,
+                "mean_ms": (time.as_millis() / timed.max(1) as u128) as u64,
+                "share": time.as_secs_f64() / total.as_secs_f64().max(f64::EPSILON),
+            })
+        })
+        .collect();
+    let slowest = stages
+        .iter()
+        .zip(totals.iter())
+        .filter(|_| timed > 0)
+        .max_by_key(|(_, time)| **time)
+        .map(|(stage, _)| stage.name.clone());
+
+    json!({
+        "timed_solves": timed,
+        "stages": stage_stats,
+        "slowest": slowest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_15_puzzle_cli::{
+        game::{handle_move_operation, GameData, GameState},
+        Operation,
+    };
+
+    #[test]
+    fn drill_stats_should_count_finished_drills() -> Result<(), Box<dyn Error>> {
+        let mut game_data =
+            GameData::from_scramble("1,2,0/4,5,3/7,8,6".parse()?, Difficulty::Expert, None);
+        game_data.mode = GameMode::Drill(Drill::RowPair);
+
+        // Sliding 3 up solves the first row, the drill is done on the first move.
+        handle_move_operation(&mut game_data, Operation::UP);
+        assert!(game_data.game_state == GameState::DONE);
+
+        let record: SolveRecord = SolveRecord::from_game(&game_data).to_string().parse()?;
+        assert!(!record.dnf);
+
+        let drills = drill_stats(&[&record]);
+        assert_eq!(drills.len(), 1);
+        assert_eq!(drills[0]["drill"], "row-pair");
+        assert_eq!(drills[0]["solves"], 1);
+        assert_eq!(drills[0]["best_moves"], 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cli_should_parse_the_default_game() -> Result<(), Box<dyn Error>> {
+        let command = Cli::try_parse_from([env!("CARGO_PKG_NAME"), "play"])?.command;
+        assert!(matches!(
+            command,
+            Some(Command::Play {
+                size: (4, 4),
+                difficulty: Difficulty::Expert,
+                daily: false,
+                goal: None,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_should_fail_impossible_moves() -> Result<(), Box<dyn Error>> {
+        let board: Board = "1,2,3/4,5,6/7,0,8".parse()?;
+
+        assert_eq!(run_verify(&board, "a", &Goal::Standard)?, EXIT_SUCCESS);
+        assert_eq!(run_verify(&board, "d", &Goal::Standard)?, EXIT_FAILURE);
+        // Nothing is below the blank to move up.
+        assert_eq!(run_verify(&board, "w", &Goal::Standard)?, EXIT_FAILURE);
+        // Moves that don't parse are invalid input, unlike a failure while running.
+        let error = run_verify(&board, "x", &Goal::Standard).unwrap_err();
+        assert!(error.is::<InvalidInput>());
+        let error = run_verify(&board, "a", &"1,0/2,3".parse()?).unwrap_err();
+        assert!(error.is::<InvalidInput>());
+
+        Ok(())
+    }
+}
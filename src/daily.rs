@@ -0,0 +1,119 @@
+//! Daily puzzle: the same scramble for everybody on a given date.
+
+use crate::board::Board;
+use crate::game::GameMode;
+use crate::scramble::shuffle_arr;
+use crate::stats::SolveRecord;
+use chrono::{Duration, Local, NaiveDate};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashSet;
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// The scramble of `date` for a board of `width` x `height`. ChaCha is used because its output,
+/// unlike `StdRng`, is guaranteed to stay the same across platforms and versions.
+pub fn daily_scramble(date: NaiveDate, width: usize, height: usize) -> Board {
+    let mut rng = ChaCha8Rng::seed_from_u64(daily_seed(date, width, height));
+
+    shuffle_arr(&mut rng, width, height)
+}
+
+/// FNV-1a of the date and size, `DefaultHasher` isn't stable between Rust releases.
+fn daily_seed(date: NaiveDate, width: usize, height: usize) -> u64 {
+    format!("{}:{}x{}", date, width, height)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Whether the ranked attempt of `date` was already used for this board size.
+pub fn attempted(records: &[SolveRecord], date: NaiveDate, width: usize, height: usize) -> bool {
+    records
+        .iter()
+        .any(|record| record.mode == GameMode::Daily(date) && record.size() == (width, height))
+}
+
+/// Number of consecutive days with a completed daily puzzle, ending today. Today not being done
+/// yet doesn't break the streak.
+pub fn streak(records: &[SolveRecord], today: NaiveDate) -> usize {
+    let completed: HashSet<NaiveDate> = records
+        .iter()
+        .filter(|record| !record.dnf)
+        .filter_map(|record| match record.mode {
+            GameMode::Daily(date) => Some(date),
+            _ => None,
+        })
+        .collect();
+
+    let mut day = if completed.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut streak = 0;
+
+    while completed.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+
+    streak
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::is_solvable;
+    use std::time::Duration as StdDuration;
+
+    fn daily_record(date: &str, dnf: bool) -> SolveRecord {
+        let date = date.parse().unwrap();
+
+        SolveRecord {
+            mode: GameMode::Daily(date),
+            dnf,
+            ..SolveRecord::new(
+                daily_scramble(date, 4, 4),
+                Vec::new(),
+                StdDuration::from_secs(1),
+            )
+        }
+    }
+
+    #[test]
+    fn daily_scramble_should_be_deterministic() {
+        let date: NaiveDate = "2020-05-01".parse().unwrap();
+        let board = daily_scramble(date, 4, 4);
+
+        assert!(is_solvable(&board));
+        assert_eq!(board, daily_scramble(date, 4, 4));
+        assert_ne!(board, daily_scramble(date.succ_opt().unwrap(), 4, 4));
+        assert_eq!(daily_scramble(date, 3, 3).width(), 3);
+    }
+
+    #[test]
+    fn streak_should_count_consecutive_days() {
+        let today: NaiveDate = "2020-05-10".parse().unwrap();
+        let records = vec![
+            daily_record("2020-05-09", false),
+            daily_record("2020-05-08", false),
+            daily_record("2020-05-07", true),
+            daily_record("2020-05-06", false),
+        ];
+
+        assert_eq!(streak(&records, today), 2);
+        assert_eq!(streak(&records, "2020-05-12".parse().unwrap()), 0);
+
+        let mut records = records;
+        records.push(daily_record("2020-05-10", false));
+        assert_eq!(streak(&records, today), 3);
+
+        assert!(attempted(&records, "2020-05-07".parse().unwrap(), 4, 4));
+        assert!(!attempted(&records, "2020-05-07".parse().unwrap(), 3, 3));
+        assert!(!attempted(&records, "2020-05-05".parse().unwrap(), 4, 4));
+    }
+}
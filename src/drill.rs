@@ -0,0 +1,212 @@
+//! Drills: positions where only part of the board is scrambled, to practice one step of the
+//! row-by-row method on its own.
+
+use crate::board::{is_solvable, Board};
+use crate::method::{stages, StageKind};
+use crate::reduction::solve_reduction;
+use rand::{seq::SliceRandom, Rng};
+use std::{error::Error, fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Drill {
+    /// The last 2x3 block, with the rest of the board solved.
+    LastBlock,
+    /// The last two tiles of the first row, with its other tiles in place.
+    RowPair,
+    /// The last two tiles of the first column, with the first row and the rest of the column
+    /// in place.
+    ColumnPair,
+}
+
+impl Drill {
+    pub const ALL: [Drill; 3] = [Drill::LastBlock, Drill::RowPair, Drill::ColumnPair];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Drill::LastBlock => "last-block",
+            Drill::RowPair => "row-pair",
+            Drill::ColumnPair => "column-pair",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Drill::LastBlock => "solve the last 2x3 block",
+            Drill::RowPair => "put the last two tiles of the first row in place",
+            Drill::ColumnPair => "put the last two tiles of the first column in place",
+        }
+    }
+
+    /// The number of stages of `method::stages` solved at the end of the drill, and the cells
+    /// left solved by the scramble.
+    fn layout(self, width: usize, height: usize) -> Result<(usize, Vec<usize>), Box<dyn Error>> {
+        let stages = stages(width, height);
+
+        let line = match self {
+            Drill::LastBlock if stages.len() >= 2 => {
+                let fixed = stages[..stages.len() - 2]
+                    .iter()
+                    .flat_map(|stage| stage.cells.iter().copied())
+                    .collect();
+
+                return Ok((stages.len(), fixed));
+            }
+            Drill::LastBlock => None,
+            Drill::RowPair => stages.iter().position(|stage| stage.kind == StageKind::Row),
+            Drill::ColumnPair => stages
+                .iter()
+                .position(|stage| stage.kind == StageKind::Column),
+        };
+        let line = line.ok_or_else(|| {
+            format!(
+                "The {} drill needs a bigger board than {}x{}",
+                self, width, height
+            )
+        })?;
+
+        let cells = &stages[line].cells;
+        let fixed = stages[..line]
+            .iter()
+            .flat_map(|stage| stage.cells.iter().copied())
+            .chain(cells[..cells.len() - 2].iter().copied())
+            .collect();
+
+        Ok((line + 1, fixed))
+    }
+
+    /// Whether the part of the board the drill is about is solved.
+    pub fn is_done(self, board: &Board) -> bool {
+        match self.layout(board.width(), board.height()) {
+            Ok((goal, _)) => stages(board.width(), board.height())[..goal]
+                .iter()
+                .all(|stage| stage.is_solved(board)),
+            Err(_) => false,
+        }
+    }
+
+    /// A random position of the drill: the cells outside of it are solved, the others shuffled
+    /// and made solvable.
+    pub fn generate<R: Rng + ?Sized>(
+        self,
+        rng: &mut R,
+        width: usize,
+        height: usize,
+    ) -> Result<Board, Box<dyn Error>> {
+        let (_, fixed) = self.layout(width, height)?;
+        let free: Vec<usize> = (0..width * height)
+            .filter(|cell| !fixed.contains(cell))
+            .collect();
+
+        loop {
+            let mut tiles = Board::solved(width, height).tiles().to_vec();
+            let mut shuffled: Vec<u16> = free.iter().map(|cell| tiles[*cell]).collect();
+            shuffled.shuffle(rng);
+            for (cell, tile) in free.iter().zip(shuffled) {
+                tiles[*cell] = tile;
+            }
+
+            let mut board = Board::new(width, height, tiles.clone())?;
+            if !is_solvable(&board) {
+                // Swapping two tiles flips the parity of the permutation.
+                let swapped: Vec<usize> = free
+                    .iter()
+                    .copied()
+                    .filter(|cell| tiles[*cell] != 0)
+                    .take(2)
+                    .collect();
+                tiles.swap(swapped[0], swapped[1]);
+                board = Board::new(width, height, tiles)?;
+            }
+
+            if !self.is_done(&board) {
+                // Makes sure the position can be solved before handing it out.
+                self.reference(&board)?;
+
+                return Ok(board);
+            }
+        }
+    }
+
+    /// Fails on boards too small to hold the drill.
+    pub fn check_size(self, width: usize, height: usize) -> Result<(), Box<dyn Error>> {
+        self.layout(width, height).map(|_| ())
+    }
+
+    /// Length of the drill's part of the row-by-row reduction solution, a reference to beat.
+    pub fn reference(self, board: &Board) -> Result<usize, Box<dyn Error>> {
+        let (goal, _) = self.layout(board.width(), board.height())?;
+        let solution = solve_reduction(board)?;
+
+        Ok(solution.phases[..goal]
+            .iter()
+            .map(|phase| phase.moves.len())
+            .sum())
+    }
+}
+
+impl fmt::Display for Drill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Drill {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Drill, Box<dyn Error>> {
+        Drill::ALL
+            .iter()
+            .copied()
+            .find(|drill| drill.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid drill '{}', expected last-block, row-pair or column-pair",
+                    s
+                )
+                .into()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{move_tile, Operation};
+
+    #[test]
+    fn drill_should_only_scramble_its_part_of_the_board() -> Result<(), Box<dyn Error>> {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..20 {
+            let board = Drill::RowPair.generate(&mut rng, 4, 4)?;
+            assert_eq!(&board.tiles()[..2], &[1, 2]);
+            assert!(!Drill::RowPair.is_done(&board));
+            assert!(is_solvable(&board));
+
+            let board = Drill::ColumnPair.generate(&mut rng, 4, 4)?;
+            assert_eq!(&board.tiles()[..5], &[1, 2, 3, 4, 5]);
+
+            let board = Drill::LastBlock.generate(&mut rng, 3, 3)?;
+            assert_eq!(&board.tiles()[..3], &[1, 2, 3]);
+            assert!(Drill::LastBlock.reference(&board)? > 0);
+        }
+
+        assert!(Drill::LastBlock.generate(&mut rng, 2, 2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn drill_should_be_done_once_its_part_is_solved() -> Result<(), Box<dyn Error>> {
+        // The first row is solved by sliding 3 up, the rest of the board doesn't matter.
+        let board: Board = "1,2,0/4,5,3/7,8,6".parse()?;
+        assert!(!Drill::RowPair.is_done(&board));
+        assert!(Drill::RowPair.is_done(&move_tile(&board, Operation::UP)));
+        assert_eq!(Drill::RowPair.reference(&board)?, 1);
+
+        assert_eq!("column-pair".parse::<Drill>()?, Drill::ColumnPair);
+        assert!("corner".parse::<Drill>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,373 @@
+//! Game state machine shared by the terminal frontends.
+
+use crate::board::{move_tile, Board, Operation};
+use crate::drill::Drill;
+use crate::scramble::{scramble_to, Difficulty};
+use chrono::NaiveDate;
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+#[allow(clippy::upper_case_acronyms, non_camel_case_types)]
+#[derive(PartialEq)]
+pub enum GameState {
+    INIT,
+    PLAYING,
+    PAUSED,
+    DONE,
+    /// Time ran out, the budget of a time attack or the inspection. Unlike `DONE` the board is
+    /// not solved.
+    TIME_OVER,
+    /// The move limit of a challenge was used up without solving the board.
+    OUT_OF_MOVES,
+    /// A blindfold solve was finished with the board still scrambled.
+    UNSOLVED,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Classic,
+    /// The daily puzzle of the given date.
+    Daily(NaiveDate),
+    /// One of the given number of puzzles solved in a row.
+    Marathon(usize),
+    /// One of the puzzles solved in a row within the given time.
+    TimeAttack(Duration),
+    /// A challenge to solve within the optimal solution length plus the given number of moves.
+    Challenge(usize),
+    /// A solve with the tiles hidden after memorizing the scramble.
+    Blindfold,
+    /// A guided solve following the row-by-row method, for practice.
+    Tutorial,
+    /// A drill, solving only part of the board.
+    Drill(Drill),
+}
+
+pub struct GameData {
+    pub game_state: GameState,
+    pub move_count: i32,
+    pub base_time: Duration,
+    pub scramble: Board,
+    pub arr_state: Board,
+    /// The layout the board is solved into.
+    pub goal: Board,
+    pub moves: Vec<Operation>,
+    /// Elapsed time of each move in `moves`.
+    pub move_times: Vec<Duration>,
+    pub start_time: Instant,
+    pub difficulty: Difficulty,
+    /// Optimal solution length of `scramble`, once known.
+    pub optimal_length: Option<usize>,
+    pub mode: GameMode,
+    /// Whether the result is recorded in stats.
+    pub ranked: bool,
+    /// Number of times the game was paused.
+    pub pauses: usize,
+}
+
+impl GameData {
+    pub fn new<R: Rng + ?Sized>(
+        rng: &mut R,
+        width: usize,
+        height: usize,
+        difficulty: Difficulty,
+    ) -> Self {
+        GameData::with_goal(rng, &Board::solved(width, height), difficulty)
+    }
+
+    /// A new game solved into `goal` instead of the usual layout.
+    pub fn with_goal<R: Rng + ?Sized>(rng: &mut R, goal: &Board, difficulty: Difficulty) -> Self {
+        let scramble = scramble_to(rng, goal, difficulty);
+
+        let mut game_data =
+            GameData::from_scramble(scramble.board, difficulty, scramble.optimal_length);
+        game_data.goal = goal.clone();
+
+        game_data
+    }
+
+    pub fn from_scramble(
+        scramble: Board,
+        difficulty: Difficulty,
+        optimal_length: Option<usize>,
+    ) -> Self {
+        GameData {
+            game_state: GameState::INIT,
+            move_count: 0,
+            base_time: Duration::from_secs(0),
+            arr_state: scramble.clone(),
+            goal: Board::solved(scramble.width(), scramble.height()),
+            scramble,
+            moves: Vec::new(),
+            move_times: Vec::new(),
+            start_time: Instant::now(),
+            difficulty,
+            optimal_length,
+            mode: GameMode::Classic,
+            ranked: true,
+            pauses: 0,
+        }
+    }
+
+    /// The same scramble from the start, a practice retry of this game.
+    pub fn retry(&self) -> Self {
+        let mut game_data =
+            GameData::from_scramble(self.scramble.clone(), self.difficulty, self.optimal_length);
+        game_data.mode = self.mode;
+        game_data.goal = self.goal.clone();
+        game_data.ranked = false;
+
+        game_data
+    }
+
+    /// Whether the board is in its goal layout.
+    pub fn is_solved(&self) -> bool {
+        self.arr_state == self.goal
+    }
+
+    /// Most moves allowed in a challenge, known once the optimal solution length is.
+    pub fn move_limit(&self) -> Option<usize> {
+        match self.mode {
+            GameMode::Challenge(extra) => self.optimal_length.map(|length| length + extra),
+            _ => None,
+        }
+    }
+
+    pub fn moves_left(&self) -> Option<usize> {
+        self.move_limit()
+            .map(|limit| limit.saturating_sub(self.move_count as usize))
+    }
+
+    pub fn elapsed_time(&self) -> Duration {
+        match self.game_state {
+            GameState::PLAYING => self.base_time + self.start_time.elapsed(),
+            _ => self.base_time,
+        }
+    }
+}
+
+pub fn handle_move_operation(game_data: &mut GameData, operation: Operation) {
+    // A challenge can't be played before its move limit is known.
+    if matches!(game_data.mode, GameMode::Challenge(_)) && game_data.move_limit().is_none() {
+        return;
+    }
+
+    let next_arr_state = move_tile(&game_data.arr_state, operation);
+
+    let finished = matches!(
+        game_data.game_state,
+        GameState::DONE | GameState::TIME_OVER | GameState::OUT_OF_MOVES | GameState::UNSOLVED
+    );
+
+    if game_data.arr_state != next_arr_state && !finished {
+        game_data.move_count += 1;
+        game_data.moves.push(operation);
+        game_data.move_times.push(game_data.elapsed_time());
+        game_data.arr_state = next_arr_state;
+    }
+
+    let mut next_game_state = handle_game_state(game_data, operation.to_char());
+    if next_game_state == GameState::PLAYING
+        && game_data.moves_left() == Some(0)
+        && !game_data.is_solved()
+    {
+        next_game_state = GameState::OUT_OF_MOVES;
+    }
+
+    game_data.base_time = update_elapsed_time(game_data, &next_game_state);
+    game_data.game_state = next_game_state;
+}
+
+/// Pauses a running game or resumes a paused one, counting the pauses.
+pub fn handle_pause(game_data: &mut GameData) {
+    let next_game_state = handle_game_state(game_data, 'p');
+
+    if next_game_state == GameState::PAUSED && game_data.game_state != GameState::PAUSED {
+        game_data.pauses += 1;
+    }
+
+    game_data.base_time = update_elapsed_time(game_data, &next_game_state);
+    game_data.game_state = next_game_state;
+}
+
+/// Ends a blindfold solve, which isn't finished by solving the board since the player can't
+/// see it. The clock stops and the board is checked.
+pub fn finish_blindfold(game_data: &mut GameData) {
+    if game_data.game_state != GameState::PLAYING {
+        return;
+    }
+
+    game_data.base_time = game_data.elapsed_time();
+    game_data.game_state = if game_data.is_solved() {
+        GameState::DONE
+    } else {
+        GameState::UNSOLVED
+    };
+}
+
+/// Applies a move made in another process. `time` is the elapsed time reported with it, the clock
+/// follows that instead of when the move arrived.
+pub fn replay_move(game_data: &mut GameData, operation: Operation, time: Duration) {
+    let move_count = game_data.move_count;
+    handle_move_operation(game_data, operation);
+
+    if game_data.move_count != move_count {
+        game_data.move_times.pop();
+        game_data.move_times.push(time);
+    }
+
+    game_data.base_time = time;
+    game_data.start_time = Instant::now();
+}
+
+pub fn handle_game_state(game_data: &GameData, char: char) -> GameState {
+    let curren_state = &game_data.game_state;
+    let arr_state = &game_data.arr_state;
+
+    // Blindfold solves are only finished when the player says so.
+    let is_done = game_data.mode != GameMode::Blindfold
+        && match game_data.mode {
+            GameMode::Drill(drill) => drill.is_done(arr_state),
+            _ => game_data.is_solved(),
+        };
+
+    match curren_state {
+        GameState::INIT => {
+            // The first move can already finish a board one move from done.
+            if !['w', 'a', 's', 'd'].contains(&char) {
+                GameState::INIT
+            } else if is_done {
+                GameState::DONE
+            } else {
+                GameState::PLAYING
+            }
+        }
+        GameState::PLAYING => {
+            if char == 'p' {
+                GameState::PAUSED
+            } else if is_done {
+                GameState::DONE
+            } else {
+                GameState::PLAYING
+            }
+        }
+        GameState::PAUSED => GameState::PLAYING,
+        GameState::DONE => {
+            if char == 'r' {
+                GameState::INIT
+            } else {
+                GameState::DONE
+            }
+        }
+        GameState::TIME_OVER => GameState::TIME_OVER,
+        GameState::OUT_OF_MOVES => GameState::OUT_OF_MOVES,
+        GameState::UNSOLVED => GameState::UNSOLVED,
+    }
+}
+
+pub fn update_elapsed_time(game_data: &GameData, next_game_state: &GameState) -> Duration {
+    let game_state = &game_data.game_state;
+    let base_time = game_data.base_time;
+    let start_time = &game_data.start_time;
+
+    let mut updated_base_time = base_time;
+
+    if game_state == &GameState::PLAYING
+        && matches!(
+            next_game_state,
+            GameState::PAUSED | GameState::DONE | GameState::OUT_OF_MOVES
+        )
+    {
+        updated_base_time = base_time + start_time.elapsed();
+    }
+
+    updated_base_time
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::parse_moves;
+    use std::error::Error;
+
+    fn challenge(optimal_length: Option<usize>) -> Result<GameData, Box<dyn Error>> {
+        let mut game_data = GameData::from_scramble(
+            "1,2,3/4,5,6/0,7,8".parse()?,
+            Difficulty::Easy,
+            optimal_length,
+        );
+        game_data.mode = GameMode::Challenge(1);
+
+        Ok(game_data)
+    }
+
+    #[test]
+    fn challenge_should_run_out_of_moves() -> Result<(), Box<dyn Error>> {
+        // Moves wait for the optimal solution length.
+        let mut game_data = challenge(None)?;
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        assert_eq!(game_data.move_count, 0);
+        assert!(game_data.game_state == GameState::INIT);
+
+        let mut game_data = challenge(Some(2))?;
+        assert_eq!(game_data.move_limit(), Some(3));
+        for operation in parse_moves("sws")? {
+            handle_move_operation(&mut game_data, operation);
+        }
+        assert_eq!(game_data.moves_left(), Some(0));
+        assert!(game_data.game_state == GameState::OUT_OF_MOVES);
+
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        assert_eq!(game_data.move_count, 3);
+
+        let mut retry = game_data.retry();
+        assert!(retry.game_state == GameState::INIT);
+        assert_eq!(retry.arr_state, game_data.scramble);
+        assert!(!retry.ranked);
+
+        // Solving within the limit passes.
+        for operation in parse_moves("aa")? {
+            handle_move_operation(&mut retry, operation);
+        }
+        assert!(retry.game_state == GameState::DONE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn first_move_should_finish_a_board_one_move_away() -> Result<(), Box<dyn Error>> {
+        let mut game_data =
+            GameData::from_scramble("1,2,3/4,5,6/7,0,8".parse()?, Difficulty::Easy, Some(1));
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        assert!(game_data.game_state == GameState::DONE);
+        assert_eq!(game_data.move_count, 1);
+
+        // Blindfold solves still wait for the player to finish.
+        let mut game_data =
+            GameData::from_scramble("1,2,3/4,5,6/7,0,8".parse()?, Difficulty::Easy, Some(1));
+        game_data.mode = GameMode::Blindfold;
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        assert!(game_data.game_state == GameState::PLAYING);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pause_should_be_counted() -> Result<(), Box<dyn Error>> {
+        let mut game_data =
+            GameData::from_scramble("1,2,3/4,5,6/0,7,8".parse()?, Difficulty::Easy, None);
+
+        // Nothing to pause before the first move.
+        handle_pause(&mut game_data);
+        assert!(game_data.game_state == GameState::INIT);
+
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        handle_pause(&mut game_data);
+        assert!(game_data.game_state == GameState::PAUSED);
+        handle_pause(&mut game_data);
+        assert!(game_data.game_state == GameState::PLAYING);
+        handle_pause(&mut game_data);
+        assert_eq!(game_data.pauses, 2);
+
+        Ok(())
+    }
+}
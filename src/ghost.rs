@@ -0,0 +1,202 @@
+//! Replays of recorded solves, raced against as a ghost.
+
+use crate::board::{is_done, manhattan_distance, move_tile, Board, Operation};
+use crate::game::GameMode;
+use crate::stats::SolveRecord;
+use std::time::Duration;
+
+/// A recorded solve replayed in real time.
+pub struct Ghost {
+    scramble: Board,
+    moves: Vec<Operation>,
+    move_times: Vec<Duration>,
+    /// Manhattan distance after each number of moves, the first entry is the scramble's.
+    distances: Vec<usize>,
+    time: Duration,
+    same_scramble: bool,
+}
+
+impl Ghost {
+    /// The ghost of the fastest solve of `scramble`, or of the fastest solve of its size when
+    /// it was never solved before.
+    pub fn best(records: &[SolveRecord], scramble: &Board) -> Option<Ghost> {
+        let solves: Vec<&SolveRecord> = records
+            .iter()
+            .filter(|record| !record.dnf && record.is_single_solve())
+            // Blindfold times include memorizing, they don't replay in real time. Drills only
+            // solve part of a board.
+            .filter(|record| !matches!(record.mode, GameMode::Blindfold | GameMode::Drill(_)))
+            .filter(|record| record.size() == (scramble.width(), scramble.height()))
+            .filter(|record| is_done(&replay(&record.scramble, &record.moves)))
+            .collect();
+
+        let best = solves
+            .iter()
+            .filter(|record| record.scramble == *scramble)
+            .min_by_key(|record| record.time)
+            .or_else(|| solves.iter().min_by_key(|record| record.time))?;
+
+        Some(Ghost::new(best, best.scramble == *scramble))
+    }
+
+    fn new(record: &SolveRecord, same_scramble: bool) -> Ghost {
+        // Records written before move times were kept get their moves spread evenly.
+        let move_times = if record.move_times.len() == record.moves.len() {
+            record.move_times.clone()
+        } else {
+            let count = record.moves.len() as u32;
+            (1..=count)
+                .map(|index| record.time * index / count)
+                .collect()
+        };
+
+        let mut board = record.scramble.clone();
+        let mut distances = vec![manhattan_distance(&board)];
+        for operation in record.moves.iter() {
+            board = move_tile(&board, *operation);
+            distances.push(manhattan_distance(&board));
+        }
+
+        Ghost {
+            scramble: record.scramble.clone(),
+            moves: record.moves.clone(),
+            move_times,
+            distances,
+            time: record.time,
+            same_scramble,
+        }
+    }
+
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Whether the ghost solves the same scramble, rather than just one of the same size.
+    pub fn same_scramble(&self) -> bool {
+        self.same_scramble
+    }
+
+    /// Number of moves the ghost made by `elapsed`.
+    pub fn moves_done(&self, elapsed: Duration) -> usize {
+        self.move_times
+            .iter()
+            .take_while(|time| **time <= elapsed)
+            .count()
+    }
+
+    pub fn board_at(&self, elapsed: Duration) -> Board {
+        replay(&self.scramble, &self.moves[..self.moves_done(elapsed)])
+    }
+
+    /// Milliseconds `board` at `elapsed` is behind the ghost, negative when ahead. Progress is
+    /// measured by Manhattan distance: the split is taken against when the ghost first got as
+    /// close to solved as `board` is.
+    pub fn behind(&self, board: &Board, elapsed: Duration) -> i64 {
+        let distance = manhattan_distance(board);
+        let reached = self
+            .distances
+            .iter()
+            .position(|ghost_distance| *ghost_distance <= distance)
+            .map(|moves| match moves {
+                0 => Duration::from_secs(0),
+                _ => self.move_times[moves - 1],
+            })
+            .unwrap_or(self.time);
+
+        elapsed.as_millis() as i64 - reached.as_millis() as i64
+    }
+}
+
+fn replay(scramble: &Board, moves: &[Operation]) -> Board {
+    moves.iter().fold(scramble.clone(), |board, operation| {
+        move_tile(&board, *operation)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::parse_moves;
+    use std::error::Error;
+
+    fn record(scramble: &str, moves: &str, times: &[u64]) -> Result<SolveRecord, Box<dyn Error>> {
+        let move_times: Vec<Duration> = times.iter().map(|ms| Duration::from_millis(*ms)).collect();
+
+        Ok(SolveRecord {
+            move_times: move_times.clone(),
+            ..SolveRecord::new(
+                scramble.parse()?,
+                parse_moves(moves)?,
+                move_times.last().copied().unwrap_or_default(),
+            )
+        })
+    }
+
+    #[test]
+    fn best_should_prefer_same_scramble() -> Result<(), Box<dyn Error>> {
+        let scramble: Board = "1,2,3/4,5,6/0,7,8".parse()?;
+        let records = vec![
+            record("1,2,3/4,5,6/0,7,8", "aa", &[1000, 3000])?,
+            record("1,2,3/4,5,6/0,7,8", "aa", &[500, 2000])?,
+            record("1,2,3/4,5,6/7,0,8", "a", &[300])?,
+            // Not a solve: the moves leave the board scrambled.
+            record("1,2,3/4,5,6/0,7,8", "a", &[100])?,
+        ];
+
+        let ghost = Ghost::best(&records, &scramble).unwrap();
+        assert!(ghost.same_scramble());
+        assert_eq!(ghost.time(), Duration::from_millis(2000));
+
+        let ghost = Ghost::best(&records, &"1,2,3/4,5,0/7,8,6".parse()?).unwrap();
+        assert!(!ghost.same_scramble());
+        assert_eq!(ghost.time(), Duration::from_millis(300));
+
+        assert!(Ghost::best(&records, &"1,2/3,0".parse()?).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ghost_should_replay_in_real_time() -> Result<(), Box<dyn Error>> {
+        let scramble: Board = "1,2,3/4,5,6/0,7,8".parse()?;
+        let ghost = Ghost::best(
+            &[record("1,2,3/4,5,6/0,7,8", "aa", &[500, 2000])?],
+            &scramble,
+        )
+        .unwrap();
+
+        assert_eq!(ghost.board_at(Duration::from_millis(499)), scramble);
+        assert_eq!(
+            ghost.board_at(Duration::from_millis(500)),
+            "1,2,3/4,5,6/7,0,8".parse()?
+        );
+        assert!(is_done(&ghost.board_at(Duration::from_secs(5))));
+
+        // Still on the scramble after one second: one second behind the ghost's start...
+        assert_eq!(ghost.behind(&scramble, Duration::from_secs(1)), 1000);
+        // ...and one move in after one second, where the ghost was at 500ms.
+        let one_move = "1,2,3/4,5,6/7,0,8".parse()?;
+        assert_eq!(ghost.behind(&one_move, Duration::from_secs(1)), 500);
+        // Solved before the ghost.
+        assert_eq!(
+            ghost.behind(&Board::solved(3, 3), Duration::from_millis(1500)),
+            -500
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn ghost_should_spread_moves_of_old_records() -> Result<(), Box<dyn Error>> {
+        let scramble: Board = "1,2,3/4,5,6/0,7,8".parse()?;
+        let mut old = record("1,2,3/4,5,6/0,7,8", "aa", &[500, 2000])?;
+        old.move_times.clear();
+
+        let ghost = Ghost::best(&[old], &scramble).unwrap();
+        assert_eq!(ghost.moves_done(Duration::from_millis(999)), 0);
+        assert_eq!(ghost.moves_done(Duration::from_millis(1000)), 1);
+        assert_eq!(ghost.moves_done(Duration::from_millis(2000)), 2);
+
+        Ok(())
+    }
+}
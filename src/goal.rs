@@ -0,0 +1,185 @@
+//! Goal layouts, the arrangements a board can be solved into. The standard goal has the tiles in
+//! order row by row with the blank last, the others move the blank or the order of the tiles.
+
+use crate::board::{check_size, Board};
+use std::{error::Error, fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Goal {
+    /// Tiles in order row by row, the blank last.
+    Standard,
+    /// Tiles in order row by row after the blank.
+    BlankFirst,
+    /// Tiles in order column by column, the blank last.
+    ColumnMajor,
+    /// Rows in order, every other one right to left, the blank last.
+    Snake,
+    /// Tiles in order clockwise from the top left corner, spiraling inwards to the blank.
+    Spiral,
+    /// Any layout, written like a board.
+    Custom(Board),
+}
+
+impl Goal {
+    pub const NAMED: [Goal; 5] = [
+        Goal::Standard,
+        Goal::BlankFirst,
+        Goal::ColumnMajor,
+        Goal::Snake,
+        Goal::Spiral,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            Goal::Standard => String::from("standard"),
+            Goal::BlankFirst => String::from("blank-first"),
+            Goal::ColumnMajor => String::from("column-major"),
+            Goal::Snake => String::from("snake"),
+            Goal::Spiral => String::from("spiral"),
+            Goal::Custom(board) => board.to_string(),
+        }
+    }
+
+    /// The solved board of this goal for a board of `width` x `height`.
+    pub fn board(&self, width: usize, height: usize) -> Result<Board, Box<dyn Error>> {
+        check_size(width, height)?;
+        let cells = width * height;
+        let order: Vec<usize> = match self {
+            Goal::Standard => (0..cells).collect(),
+            Goal::BlankFirst => {
+                let mut order: Vec<usize> = (1..cells).collect();
+                order.push(0);
+
+                order
+            }
+            Goal::ColumnMajor => (0..width)
+                .flat_map(|column| (0..height).map(move |row| row * width + column))
+                .collect(),
+            Goal::Snake => (0..height)
+                .flat_map(|row| {
+                    let cells = (0..width).map(move |column| row * width + column);
+
+                    if row % 2 == 0 {
+                        cells.collect::<Vec<usize>>()
+                    } else {
+                        cells.rev().collect()
+                    }
+                })
+                .collect(),
+            Goal::Spiral => spiral(width, height),
+            Goal::Custom(board) => {
+                if (board.width(), board.height()) != (width, height) {
+                    return Err(
+                        format!("The goal {} is not a {}x{} board", board, width, height).into(),
+                    );
+                }
+
+                return Ok(board.clone());
+            }
+        };
+
+        // Tiles follow the order of the cells, the blank takes the last one.
+        let mut tiles = vec![0; cells];
+        for (tile, cell) in order[..cells - 1].iter().enumerate() {
+            tiles[*cell] = tile as u16 + 1;
+        }
+
+        Board::new(width, height, tiles)
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Goal {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Goal, Box<dyn Error>> {
+        if let Some(goal) = Goal::NAMED.iter().find(|goal| goal.name() == s) {
+            return Ok(goal.clone());
+        }
+
+        match s.parse() {
+            Ok(board) => Ok(Goal::Custom(board)),
+            Err(_) => Err(format!(
+                "Invalid goal '{}', expected standard, blank-first, column-major, snake, spiral \
+                 or a board",
+                s
+            )
+            .into()),
+        }
+    }
+}
+
+/// Cells clockwise from the top left corner, going round the border and then inwards.
+fn spiral(width: usize, height: usize) -> Vec<usize> {
+    let (mut top, mut bottom, mut left, mut right) = (0, height - 1, 0, width - 1);
+    let mut order = Vec::new();
+
+    loop {
+        order.extend((left..=right).map(|column| top * width + column));
+        top += 1;
+        if top > bottom {
+            break;
+        }
+
+        order.extend((top..=bottom).map(|row| row * width + right));
+        if right == left {
+            break;
+        }
+        right -= 1;
+
+        order.extend((left..=right).rev().map(|column| bottom * width + column));
+        if bottom == top {
+            break;
+        }
+        bottom -= 1;
+
+        order.extend((top..=bottom).rev().map(|row| row * width + left));
+        left += 1;
+        if left > right {
+            break;
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goal_should_lay_out_the_tiles() -> Result<(), Box<dyn Error>> {
+        assert_eq!(Goal::Standard.board(3, 3)?, Board::solved(3, 3));
+        assert_eq!(Goal::BlankFirst.board(3, 2)?.to_string(), "0,1,2/3,4,5");
+        assert_eq!(Goal::ColumnMajor.board(3, 2)?.to_string(), "1,3,5/2,4,0");
+        assert_eq!(Goal::Snake.board(3, 3)?.to_string(), "1,2,3/6,5,4/7,8,0");
+        assert_eq!(Goal::Spiral.board(3, 3)?.to_string(), "1,2,3/8,0,4/7,6,5");
+        assert_eq!(
+            Goal::Spiral.board(4, 4)?.to_string(),
+            "1,2,3,4/12,13,14,5/11,0,15,6/10,9,8,7"
+        );
+        assert_eq!(Goal::Spiral.board(4, 2)?.to_string(), "1,2,3,4/0,7,6,5");
+        assert_eq!(Goal::Spiral.board(2, 3)?.to_string(), "1,2/0,3/5,4");
+
+        Ok(())
+    }
+
+    #[test]
+    fn goal_should_parse_names_and_boards() -> Result<(), Box<dyn Error>> {
+        assert_eq!("snake".parse::<Goal>()?, Goal::Snake);
+
+        let goal: Goal = "2,1/3,0".parse()?;
+        assert_eq!(goal.board(2, 2)?.to_string(), "2,1/3,0");
+        assert!(goal.board(3, 3).is_err());
+        assert!(Goal::Standard.board(1, 1).is_err());
+        assert!(Goal::Spiral.board(0, 3).is_err());
+        assert!("zigzag".parse::<Goal>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use rust_15_puzzle_cli::{analysis::Analysis, game::GameData, Board, Operation};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// A finished solve: scramble, goal, moves, their times, the total time and its cancel flag.
+type Solve = (
+    Board,
+    Board,
+    Vec<Operation>,
+    Vec<Duration>,
+    Duration,
+    Arc<AtomicBool>,
+);
+
+/// Analyzes finished solves on a background thread. Like `OptimalSolver` only the latest
+/// request is analyzed: a new request cancels the running analysis.
+pub struct Analyzer {
+    tx: mpsc::Sender<Solve>,
+    rx: mpsc::Receiver<(Board, Analysis)>,
+    /// Stops the analysis of the latest request.
+    cancel: Arc<AtomicBool>,
+}
+
+impl Analyzer {
+    pub fn new() -> Analyzer {
+        let (tx, request_rx) = mpsc::channel::<Solve>();
+        let (result_tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            while let Ok(solve) = request_rx.recv() {
+                let (scramble, goal, moves, move_times, time, cancel) =
+                    request_rx.try_iter().last().unwrap_or(solve);
+
+                if let Ok(Some(analysis)) =
+                    Analysis::new(&scramble, &goal, &moves, &move_times, time, &cancel)
+                {
+                    if result_tx.send((scramble, analysis)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Analyzer {
+            tx,
+            rx,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn request(&mut self, game_data: &GameData) {
+        self.cancel();
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.tx
+            .send((
+                game_data.scramble.clone(),
+                game_data.goal.clone(),
+                game_data.moves.clone(),
+                game_data.move_times.clone(),
+                game_data.base_time,
+                Arc::clone(&self.cancel),
+            ))
+            .unwrap();
+    }
+
+    /// Stops the analysis of the latest request, its result is never sent.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the analysis of the solve of `scramble` if it finished since the last poll.
+    pub fn poll(&self, scramble: &Board) -> Option<Analysis> {
+        self.rx
+            .try_iter()
+            .filter(|(analyzed, _)| analyzed == scramble)
+            .map(|(_, analysis)| analysis)
+            .last()
+    }
+}
@@ -1,48 +1,49 @@
-use crate::helper::{GameState, ThemeSystem};
-use std::error::Error;
+use crate::helper::ThemeSystem;
+use rust_15_puzzle_cli::{
+    analysis::{Analysis, ANALYSIS_MAX_CELLS},
+    game::{GameData, GameMode, GameState},
+    ghost::Ghost,
+    goal::Goal,
+    scramble::OPTIMAL_SCRAMBLE_MAX_CELLS,
+    timing::{long_pauses, move_intervals},
+    Board,
+};
+use std::{error::Error, time::Instant};
 use tui::{
     backend::Backend,
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, BorderType, Borders, Paragraph, Text},
+    widgets::{Block, BorderType, Borders, Paragraph, Sparkline, Text},
     Frame,
 };
 
+/// Draws the tiles of `board`. Hidden boards only show the borders of the tiles, the blank is
+/// left out. Tiles in their cell of `goal` are highlighted as placed, tiles in `targets` as the
+/// ones to place next.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_board<B>(
-    arr: &[u16; 16],
+    board: &Board,
     frame: &mut Frame<B>,
     area: &Rect,
     length: u16,
     theme_system: &ThemeSystem,
+    hidden: bool,
+    targets: &[u16],
+    goal: Option<&Board>,
 ) -> Result<(), Box<dyn Error>>
 where
     B: Backend,
 {
-    let board = [
-        (0, 0),
-        (1, 0),
-        (2, 0),
-        (3, 0),
-        (0, 1),
-        (1, 1),
-        (2, 1),
-        (3, 1),
-        (0, 2),
-        (1, 2),
-        (2, 2),
-        (3, 2),
-        (0, 3),
-        (1, 3),
-        (2, 3),
-        (3, 3),
-    ];
+    let board_width = board.width();
 
     let color_tile_default_border = theme_system.get_color_tile_default_border();
     let color_tile_text = theme_system.get_color_tile_text();
     let color_tile_selected_border = theme_system.get_color_tile_selected_border();
+    let color_tile_target_border = theme_system.get_color_tile_target_border();
 
-    board.iter().zip(arr.iter()).enumerate().for_each(|x| {
-        let (index, (multiplier, number)) = x;
+    board.tiles().iter().enumerate().for_each(|x| {
+        let (index, number) = x;
+        let multiplier = ((index % board_width) as u16, (index / board_width) as u16);
         let width = length + 3;
         let height = length;
         let area = Rect::new(
@@ -52,8 +53,17 @@ where
             height,
         );
 
-        let style_selected = Style::default().fg(if index as u16 + 1 == *number && *number != 0 {
+        if hidden && *number == 0 {
+            return;
+        }
+
+        let in_place = goal.is_some_and(|goal| goal.tiles()[index] == *number) && *number != 0;
+        let style_selected = Style::default().fg(if hidden {
+            color_tile_default_border
+        } else if in_place {
             color_tile_selected_border
+        } else if targets.contains(number) {
+            color_tile_target_border
         } else {
             color_tile_default_border
         });
@@ -63,10 +73,12 @@ where
             .border_type(BorderType::Rounded)
             .border_style(style_selected);
 
-        let number_string = if *number == 0 {
+        let number_string = if *number == 0 || hidden {
             String::from("")
-        } else {
+        } else if length > 3 {
             format!("\n{}", number)
+        } else {
+            number.to_string()
         };
 
         let text = [Text::styled(
@@ -84,19 +96,372 @@ where
     Ok(())
 }
 
-pub fn draw_guide<B>(frame: &mut Frame<B>, area: &Rect) -> Result<(), Box<dyn Error>>
+/// Tile length used by `draw_board`, smaller tiles for bigger boards.
+pub fn tile_length(board: &Board) -> u16 {
+    if board.width().max(board.height()) <= 4 {
+        5
+    } else {
+        3
+    }
+}
+
+/// A board with its time, move count and mode in the title, `player` names it in races.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_game<B>(
+    frame: &mut Frame<B>,
+    area: &Rect,
+    game_data: &mut GameData,
+    player: Option<&str>,
+    length: u16,
+    theme_system: &ThemeSystem,
+    hidden: bool,
+    targets: &[u16],
+) -> Result<(), Box<dyn Error>>
 where
     B: Backend,
 {
-    let guide = r#"    
+    let time = display_time(game_data);
+
+    let mode = match game_data.mode {
+        GameMode::Daily(_) => String::from("daily"),
+        GameMode::Marathon(_) => format!("marathon, {}", game_data.difficulty),
+        GameMode::TimeAttack(_) => format!("time attack, {}", game_data.difficulty),
+        GameMode::Challenge(extra) => format!("challenge +{}, {}", extra, game_data.difficulty),
+        GameMode::Blindfold => format!("blindfold, {}", game_data.difficulty),
+        GameMode::Tutorial => format!("tutorial, {}", game_data.difficulty),
+        GameMode::Drill(drill) => format!("drill, {}", drill),
+        GameMode::Classic => game_data.difficulty.to_string(),
+    };
+    let player = player.map(|name| format!(" {}", name)).unwrap_or_default();
+    // Time attacks count down what is left of the budget.
+    let clock = match game_data.mode {
+        GameMode::TimeAttack(budget) => format!(
+            "Left: {}s",
+            budget.saturating_sub(game_data.elapsed_time()).as_secs()
+        ),
+        _ => format!("Time: {}s", time),
+    };
+    // Challenges count down the moves left instead, unknown until the scramble is solved.
+    let moves = match (game_data.mode, game_data.moves_left()) {
+        (GameMode::Challenge(_), Some(left)) => format!("Moves left: {}", left),
+        (GameMode::Challenge(_), None) => String::from("Moves left: ?"),
+        _ => format!("Moves: {}", game_data.move_count),
+    };
+    let title_string = format!("{} {}  {}  [{}]", player, clock, moves, mode);
+
+    let block = Block::default()
+        .borders(Borders::NONE)
+        .title(title_string.as_str())
+        .title_style(Style::default().modifier(Modifier::BOLD));
+    frame.render_widget(block, *area);
+
+    let board_area = area.inner(&Margin {
+        horizontal: 1,
+        vertical: 2,
+    });
+
+    // The board is hidden while paused, so the pause can't be used to plan ahead.
+    if game_data.game_state == GameState::PAUSED {
+        let board = &game_data.arr_state;
+        let board_area = Rect::new(
+            board_area.x,
+            board_area.y,
+            board.width() as u16 * (length + 3),
+            board.height() as u16 * length,
+        )
+        .intersection(board_area);
+        let lines = [
+            format!("Elapsed: {:.1}s", game_data.base_time.as_secs_f64()),
+            String::from("Press 'p' to resume"),
+        ];
+
+        return draw_message(frame, &board_area, " Paused ", &lines);
+    }
+
+    draw_board(
+        &game_data.arr_state,
+        frame,
+        &board_area,
+        length,
+        theme_system,
+        hidden,
+        targets,
+        Some(&game_data.goal),
+    )
+}
+
+/// A framed message drawn in place of a board, like the final screen of a time attack.
+pub fn draw_message<B>(
+    frame: &mut Frame<B>,
+    area: &Rect,
+    title: &str,
+    lines: &[String],
+) -> Result<(), Box<dyn Error>>
+where
+    B: Backend,
+{
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(title)
+        .title_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD));
+
+    let text: Vec<Text> = lines
+        .iter()
+        .map(|line| Text::raw(format!("\n {}", line)))
+        .collect();
+    let paragraph = Paragraph::new(text.iter())
+        .block(block)
+        .alignment(Alignment::Left)
+        .wrap(true);
+
+    frame.render_widget(paragraph, *area);
+
+    Ok(())
+}
+
+/// The ghost board, dimmed, at the time of `game_data`. The title tells how far ahead or behind
+/// of the ghost the player is.
+pub fn draw_ghost<B>(
+    frame: &mut Frame<B>,
+    area: &Rect,
+    ghost: &Ghost,
+    game_data: &GameData,
+    length: u16,
+    theme_system: &ThemeSystem,
+) -> Result<(), Box<dyn Error>>
+where
+    B: Backend,
+{
+    let elapsed = game_data.elapsed_time();
+    let split = match ghost.behind(&game_data.arr_state, elapsed) {
+        0 => String::from("even"),
+        behind if behind > 0 => format!("{:.1}s behind", behind as f64 / 1000.0),
+        ahead => format!("{:.1}s ahead", -ahead as f64 / 1000.0),
+    };
+    let title_string = format!(
+        " Ghost Time: {}s  Moves: {}  [{}]",
+        elapsed.min(ghost.time()).as_secs(),
+        ghost.moves_done(elapsed),
+        split
+    );
+
+    let block = Block::default()
+        .borders(Borders::NONE)
+        .title(title_string.as_str())
+        .title_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(block, *area);
+
+    let board = ghost.board_at(elapsed);
+    draw_board(
+        &board,
+        frame,
+        &area.inner(&Margin {
+            horizontal: 1,
+            vertical: 2,
+        }),
+        length,
+        &theme_system.dimmed(),
+        false,
+        &[],
+        Some(&Board::solved(board.width(), board.height())),
+    )
+}
+
+/// The layout to solve into, dimmed, next to boards solved into another goal than the usual one.
+pub fn draw_goal<B>(
+    frame: &mut Frame<B>,
+    area: &Rect,
+    goal: &Goal,
+    board: &Board,
+    length: u16,
+    theme_system: &ThemeSystem,
+) -> Result<(), Box<dyn Error>>
+where
+    B: Backend,
+{
+    let title_string = format!(" Goal: {}", goal);
+
+    let block = Block::default()
+        .borders(Borders::NONE)
+        .title(title_string.as_str())
+        .title_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(block, *area);
+
+    draw_board(
+        board,
+        frame,
+        &area.inner(&Margin {
+            horizontal: 1,
+            vertical: 2,
+        }),
+        length,
+        &theme_system.dimmed(),
+        false,
+        &[],
+        None,
+    )
+}
+
+/// Seconds shown in the title. The clock only starts with the first move, so `start_time` is
+/// held at now until then and while paused.
+fn display_time(game_data: &mut GameData) -> u64 {
+    match game_data.game_state {
+        GameState::INIT => {
+            game_data.start_time = Instant::now();
+
+            0
+        }
+        GameState::PLAYING => game_data.elapsed_time().as_secs(),
+        GameState::PAUSED => {
+            game_data.start_time = Instant::now();
+
+            game_data.base_time.as_secs()
+        }
+        GameState::DONE | GameState::TIME_OVER | GameState::OUT_OF_MOVES | GameState::UNSOLVED => {
+            game_data.base_time.as_secs()
+        }
+    }
+}
+
+/// The analysis of a finished solve, drawn in place of the guide. The timeline marks wasted
+/// moves, the ones that took the board further from solved.
+pub fn draw_analysis<B>(
+    frame: &mut Frame<B>,
+    area: &Rect,
+    game_data: &GameData,
+    analysis: Option<&Analysis>,
+) -> Result<(), Box<dyn Error>>
+where
+    B: Backend,
+{
+    let block = Block::default()
+        .borders(Borders::NONE)
+        .title("Analysis")
+        .title_style(Style::default().modifier(Modifier::BOLD));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(7),
+                Constraint::Length(6),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(*area);
+
+    let board = &game_data.scramble;
+    let stats = [Text::raw(match analysis {
+        Some(analysis) => format!(
+            "\n\nMoves: {}, optimal {} (+{})\nEfficiency: {:.1}%\nTPS: {:.2}\nWasted moves: {}, {:.1}s spent on them",
+            analysis.moves().len(),
+            analysis.optimal_length(),
+            analysis.moves().len() - analysis.optimal_length(),
+            analysis.efficiency(),
+            analysis.tps(),
+            analysis.wasted().iter().filter(|wasted| **wasted).count(),
+            analysis.wasted_time().as_secs_f64()
+        ),
+        None if board.width() * board.height() <= ANALYSIS_MAX_CELLS => {
+            String::from("\n\nComparing the solve to the optimal solution...")
+        }
+        None => format!(
+            "\n\nMoves: {}\nOnly boards of up to {} cells are compared to the optimal solution",
+            game_data.moves.len(),
+            ANALYSIS_MAX_CELLS
+        ),
+    })];
+    let paragraph = Paragraph::new(stats.iter())
+        .block(block)
+        .alignment(Alignment::Left)
+        .wrap(true);
+    frame.render_widget(paragraph, chunks[0]);
+
+    // Timing only needs the moves, it is shown before the comparison is done.
+    let intervals = move_intervals(&game_data.move_times);
+    let data: Vec<u64> = intervals
+        .iter()
+        .map(|interval| interval.as_millis() as u64)
+        .collect();
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .title("Time between moves"),
+        )
+        .data(&data)
+        .style(Style::default().fg(Color::Yellow));
+    frame.render_widget(sparkline, chunks[1]);
+
+    let mut text = Vec::new();
+    if let Some(analysis) = analysis {
+        text.push(Text::raw("Timeline, wasted moves in red:\n"));
+        for (operation, wasted) in analysis.moves().iter().zip(analysis.wasted().iter()) {
+            let style = if *wasted {
+                Style::default().fg(Color::Red).modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+
+            text.push(Text::styled(operation.to_char().to_string(), style));
+        }
+    }
+
+    let pauses = long_pauses(&intervals);
+    if !pauses.is_empty() {
+        text.push(Text::raw("\n\nLong pauses:"));
+        for index in pauses {
+            text.push(Text::raw(format!(
+                "\n    before move {} ({:.1}s)",
+                index + 1,
+                intervals[index].as_secs_f64()
+            )));
+        }
+    }
+
+    let paragraph = Paragraph::new(text.iter())
+        .alignment(Alignment::Left)
+        .wrap(true);
+    frame.render_widget(paragraph, chunks[2]);
+
+    Ok(())
+}
+
+/// The list of commands, `extra_commands` are the ones of the game mode.
+pub fn draw_guide<B>(
+    frame: &mut Frame<B>,
+    area: &Rect,
+    race: bool,
+    extra_commands: &[&str],
+) -> Result<(), Box<dyn Error>>
+where
+    B: Backend,
+{
+    let moves = if race {
+        "Player 1 move: w,s,a,d\n    Player 2 move: ↑,↓,←,→"
+    } else {
+        "Move: ↑,↓,←,→ or w,s,a,d"
+    };
+    let extra: String = extra_commands
+        .iter()
+        .map(|command| format!("\n    {}", command))
+        .collect();
+    let guide = format!(
+        r#"    
 
 Commands 
-    Move: ↑,↓,←,→ or w,s,a,d
+    {}
     Quit : q
-    New game : r
+    New game : r{}
+    New game (easy, medium, hard, expert) : 1, 2, 3, 4
     Pause : p
     Change ColorTheme: c
-    "#;
+    "#,
+        moves, extra
+    );
 
     let block = Block::default()
         .borders(Borders::NONE)
@@ -120,7 +485,9 @@ Commands
 pub fn draw_header<B>(
     frame: &mut Frame<B>,
     area: &Rect,
-    game_state: &GameState,
+    game_data: &GameData,
+    status: &str,
+    banner: Option<&str>,
 ) -> Result<(), Box<dyn Error>>
 where
     B: Backend,
@@ -129,24 +496,41 @@ where
         .borders(Borders::NONE)
         .border_style(Style::default().fg(Color::Yellow));
 
-    let data = match game_state {
-        GameState::INIT => {
-            "\n To start, press move key! \n If you can't see the board, press 'c' to change Theme!"
+    let game_state = &game_data.game_state;
+    let board = &game_data.scramble;
+
+    let data = match (banner, game_state) {
+        (Some(banner), _) => format!("\n {}", banner),
+        (_, GameState::INIT) => String::from(
+            "\n To start, press move key! \n If you can't see the board, press 'c' to change Theme!",
+        ),
+        (_, GameState::PAUSED) => String::from("\n PAUSED"),
+        (_, GameState::DONE) => {
+            let optimal = match game_data.optimal_length {
+                Some(length) => format!("optimal {} moves", length),
+                None if board.width() * board.height() <= OPTIMAL_SCRAMBLE_MAX_CELLS => {
+                    String::from("optimal solution still being computed...")
+                }
+                None => String::from("board too big to compute the optimal solution"),
+            };
+
+            format!(
+                "\n Excellent! Press 'r' to start new game!\n You: {} moves, {}",
+                game_data.move_count, optimal
+            )
         }
-        GameState::PAUSED => "\n PAUSED",
-        GameState::DONE => "\n Excellent! Press 'r' to start new game!",
-        _ => "",
+        _ => String::new(),
     };
 
     let text = [Text::styled(
         data,
-        Style::default()
-            .fg(Color::Yellow)
-            .modifier(if game_state == &GameState::DONE {
+        Style::default().fg(Color::Yellow).modifier(
+            if game_state == &GameState::DONE || banner.is_some() {
                 Modifier::SLOW_BLINK | Modifier::BOLD
             } else {
                 Modifier::empty() | Modifier::BOLD
-            }),
+            },
+        ),
     )];
     let paragraph = Paragraph::new(text.iter())
         .block(block)
@@ -154,5 +538,15 @@ where
 
     frame.render_widget(paragraph, *area);
 
+    // Mode specific status goes on the last line of the header.
+    if area.height >= 4 {
+        let text = [Text::styled(
+            format!(" {}", status),
+            Style::default().fg(Color::Yellow),
+        )];
+        let status_area = Rect::new(area.x, area.y + 3, area.width, 1);
+        frame.render_widget(Paragraph::new(text.iter()), status_area);
+    }
+
     Ok(())
 }
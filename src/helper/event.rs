@@ -10,9 +10,17 @@ use std::time::Duration;
 use termion::event::Key;
 use termion::input::TermRead;
 
+use rust_15_puzzle_cli::net::{Message, MessageReader};
+
+use super::SolverEvent;
+
 pub enum Event<I> {
     Input(I),
     Tick,
+    /// A message from the opponent of a networked race.
+    Remote(Message),
+    /// News from the background optimal solver.
+    Solver(SolverEvent),
 }
 
 /// A small event handler that wrap termion input and tick events. Each event
@@ -20,6 +28,7 @@ pub enum Event<I> {
 #[allow(dead_code)]
 pub struct Events {
     rx: mpsc::Receiver<Event<Key>>,
+    tx: mpsc::Sender<Event<Key>>,
 
     tick_handle: thread::JoinHandle<()>,
 }
@@ -52,17 +61,12 @@ impl Events {
             let ignore_exit_key = ignore_exit_key.clone();
             thread::spawn(move || {
                 let stdin = io::stdin();
-                for evt in stdin.keys() {
-                    match evt {
-                        Ok(key) => {
-                            if let Err(_) = tx.send(Event::Input(key)) {
-                                return;
-                            }
-                            if !ignore_exit_key.load(Ordering::Relaxed) && key == config.exit_key {
-                                return;
-                            }
-                        }
-                        Err(_) => {}
+                for key in stdin.keys().flatten() {
+                    if tx.send(Event::Input(key)).is_err() {
+                        return;
+                    }
+                    if !ignore_exit_key.load(Ordering::Relaxed) && key == config.exit_key {
+                        return;
                     }
                 }
             })
@@ -77,7 +81,33 @@ impl Events {
                 }
             })
         };
-        Events { rx, tick_handle }
+        Events {
+            rx,
+            tx,
+            tick_handle,
+        }
+    }
+
+    /// Lets other threads push their own events into the loop.
+    pub fn sender(&self) -> mpsc::Sender<Event<Key>> {
+        self.tx.clone()
+    }
+
+    /// Passes the messages of another process to the loop, a closed connection becomes a `Bye`.
+    pub fn forward_messages(&self, mut reader: MessageReader) {
+        let tx = self.sender();
+
+        thread::spawn(move || loop {
+            let message = match reader.read() {
+                Ok(Some(message)) => message,
+                _ => Message::Bye,
+            };
+            let bye = message == Message::Bye;
+
+            if tx.send(Event::Remote(message)).is_err() || bye {
+                return;
+            }
+        });
     }
 
     pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
@@ -1,7 +1,11 @@
+mod analyzer;
 mod draw;
 mod event;
-mod util;
+mod optimal;
+mod theme;
 
+pub use analyzer::*;
 pub use draw::*;
 pub use event::*;
-pub use util::*;
+pub use optimal::*;
+pub use theme::*;
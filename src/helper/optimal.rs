@@ -0,0 +1,83 @@
+use super::Event;
+use rust_15_puzzle_cli::{
+    heuristic::{Heuristic, PATTERN_MAX_CELLS},
+    solve_cancellable, Board, Progress,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+use termion::event::Key;
+
+/// What the background solver has to say about the board it was asked to solve.
+pub enum SolverEvent {
+    Progress(Board, Progress),
+    Solved(Board, usize),
+    Cancelled(Board),
+}
+
+/// Solves boards on a background thread so the game keeps running meanwhile, sending progress
+/// and results to the game loop as events. Only the latest requested board is solved: a new
+/// request cancels the running search and drops the ones queued behind it.
+pub struct OptimalSolver {
+    /// Boards to solve, their goal and their cancel flag.
+    tx: mpsc::Sender<(Board, Board, Arc<AtomicBool>)>,
+    /// Stops the search of the latest request.
+    cancel: Arc<AtomicBool>,
+}
+
+impl OptimalSolver {
+    pub fn new(events: mpsc::Sender<Event<Key>>) -> OptimalSolver {
+        let (tx, request_rx) = mpsc::channel::<(Board, Board, Arc<AtomicBool>)>();
+
+        thread::spawn(move || {
+            while let Ok(request) = request_rx.recv() {
+                let (board, goal, cancel) = request_rx.try_iter().last().unwrap_or(request);
+
+                let mut report = |progress| {
+                    let _ = events.send(Event::Solver(SolverEvent::Progress(
+                        board.clone(),
+                        progress,
+                    )));
+                };
+                // The pattern database takes a second to build but searches 4x4 far faster.
+                let heuristic = if board.width() * board.height() <= PATTERN_MAX_CELLS {
+                    Heuristic::PatternDatabase
+                } else {
+                    Heuristic::LinearConflict
+                };
+                let event = match solve_cancellable(&board, &goal, heuristic, &cancel, &mut report)
+                {
+                    Ok(Some(solution)) => SolverEvent::Solved(board, solution.moves.len()),
+                    Ok(None) => SolverEvent::Cancelled(board),
+                    Err(_) => continue,
+                };
+
+                if events.send(Event::Solver(event)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        OptimalSolver {
+            tx,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn request(&mut self, board: &Board, goal: &Board) {
+        self.cancel();
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.tx
+            .send((board.clone(), goal.clone(), Arc::clone(&self.cancel)))
+            .unwrap();
+    }
+
+    /// Stops the search of the latest request, a `Cancelled` event follows.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,77 @@
+use tui::style::Color;
+
+#[derive(Clone, Copy)]
+pub enum ThemeMode {
+    LightMode,
+    DarkMode,
+}
+
+pub struct ThemeSystem {
+    mode: ThemeMode,
+    /// Faded colors, for boards in the background like a ghost.
+    dimmed: bool,
+}
+
+impl ThemeSystem {
+    pub fn new(mode: ThemeMode) -> ThemeSystem {
+        ThemeSystem {
+            mode,
+            dimmed: false,
+        }
+    }
+
+    pub fn change_theme(self) -> ThemeSystem {
+        match self.mode {
+            ThemeMode::LightMode => ThemeSystem {
+                mode: ThemeMode::DarkMode,
+                ..self
+            },
+            ThemeMode::DarkMode => ThemeSystem {
+                mode: ThemeMode::LightMode,
+                ..self
+            },
+        }
+    }
+
+    /// The same theme with faded colors.
+    pub fn dimmed(&self) -> ThemeSystem {
+        ThemeSystem {
+            mode: self.mode,
+            dimmed: true,
+        }
+    }
+
+    pub fn get_color_tile_text(&self) -> Color {
+        match (&self.mode, self.dimmed) {
+            (ThemeMode::LightMode, false) => Color::Black,
+            (ThemeMode::DarkMode, false) => Color::White,
+            (ThemeMode::LightMode, true) => Color::Gray,
+            (ThemeMode::DarkMode, true) => Color::DarkGray,
+        }
+    }
+
+    pub fn get_color_tile_default_border(&self) -> Color {
+        match (&self.mode, self.dimmed) {
+            (ThemeMode::LightMode, false) => Color::Black,
+            (ThemeMode::DarkMode, false) => Color::White,
+            (ThemeMode::LightMode, true) => Color::Gray,
+            (ThemeMode::DarkMode, true) => Color::DarkGray,
+        }
+    }
+
+    /// Tiles to place next, like the ones of the current stage of the tutorial.
+    pub fn get_color_tile_target_border(&self) -> Color {
+        match self.mode {
+            ThemeMode::LightMode => Color::Blue,
+            ThemeMode::DarkMode => Color::Yellow,
+        }
+    }
+
+    /// Tiles in place stay highlighted on dimmed boards too.
+    pub fn get_color_tile_selected_border(&self) -> Color {
+        match self.mode {
+            ThemeMode::LightMode => Color::LightRed,
+            ThemeMode::DarkMode => Color::Green,
+        }
+    }
+}
@@ -0,0 +1,415 @@
+//! Lower bounds of the solution length the optimal solver can search with, and the tables some
+//! of them need. Tables are built once per goal and shared by every later search.
+
+use crate::board::Board;
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    error::Error,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// Pattern databases are only built for boards up to this many cells, 4x4 included.
+pub const PATTERN_MAX_CELLS: usize = 16;
+
+/// Tiles in the biggest pattern, a 4x4 board is split into three patterns of five.
+const PATTERN_MAX_TILES: usize = 5;
+
+/// Marks pattern positions not reached yet while searching.
+const UNKNOWN: u8 = u8::MAX;
+
+/// Tables already built, by goal.
+type Cache<T> = OnceLock<Mutex<HashMap<Board, Arc<T>>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heuristic {
+    /// Sum of the distances of each tile to its goal.
+    Manhattan,
+    /// Manhattan distance plus two moves for each tile that must leave its row or column to let
+    /// another one pass.
+    LinearConflict,
+    /// Moves needed to bring each tile to its goal row, counting the tiles the blank must swap
+    /// with between rows, plus the same for columns.
+    WalkingDistance,
+    /// Exact moves of disjoint groups of tiles, searched in advance and added up.
+    PatternDatabase,
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 4] = [
+        Heuristic::Manhattan,
+        Heuristic::LinearConflict,
+        Heuristic::WalkingDistance,
+        Heuristic::PatternDatabase,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heuristic::Manhattan => "manhattan",
+            Heuristic::LinearConflict => "linear-conflict",
+            Heuristic::WalkingDistance => "walking-distance",
+            Heuristic::PatternDatabase => "pattern-database",
+        }
+    }
+
+    /// Fails on boards too big for the tables of the heuristic.
+    pub fn check_size(self, width: usize, height: usize) -> Result<(), Box<dyn Error>> {
+        if self == Heuristic::PatternDatabase && width * height > PATTERN_MAX_CELLS {
+            return Err(format!(
+                "Pattern databases are only built for boards of up to {} cells",
+                PATTERN_MAX_CELLS
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Heuristic {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Heuristic, Box<dyn Error>> {
+        Heuristic::ALL
+            .iter()
+            .copied()
+            .find(|heuristic| heuristic.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid heuristic '{}', expected manhattan, linear-conflict, \
+                     walking-distance or pattern-database",
+                    s
+                )
+                .into()
+            })
+    }
+}
+
+/// Walking distance tables of a board size, one for rows and one for columns.
+pub struct WalkingDistance {
+    pub rows: WalkingTable,
+    pub columns: WalkingTable,
+}
+
+impl WalkingDistance {
+    /// The tables of the goal, built the first time they are asked for. Only the line of the
+    /// goal's blank matters, the tiles of each line all belong to it.
+    pub fn get(goal: &Board) -> Result<Arc<WalkingDistance>, Box<dyn Error>> {
+        static CACHE: Cache<WalkingDistance> = OnceLock::new();
+
+        let (width, height, blank) = (goal.width(), goal.height(), goal.blank_index());
+        cached(&CACHE, goal, || {
+            Ok(WalkingDistance {
+                rows: WalkingTable::build(height, width, blank / width)?,
+                columns: WalkingTable::build(width, height, blank % width)?,
+            })
+        })
+    }
+}
+
+/// Distances of every way to spread the tiles among the lines of one direction. A state counts,
+/// for each line, how many of its tiles belong to each goal line, plus the line of the blank.
+/// It is packed in a `u64`, `bits` per count.
+pub struct WalkingTable {
+    lines: usize,
+    bits: u32,
+    distances: HashMap<u64, u8>,
+}
+
+impl WalkingTable {
+    /// Searches every state of `lines` lines of `length` cells back from the solved one, which
+    /// has the blank in line `blank`.
+    fn build(lines: usize, length: usize, blank: usize) -> Result<WalkingTable, Box<dyn Error>> {
+        let bits = usize::BITS - length.max(lines - 1).leading_zeros();
+        if (lines * lines + 1) as u32 * bits > u64::BITS {
+            return Err(format!(
+                "Walking distance tables are too big for lines of {} cells",
+                length
+            )
+            .into());
+        }
+
+        let mut table = WalkingTable {
+            lines,
+            bits,
+            distances: HashMap::new(),
+        };
+
+        // The line of the blank holds one tile less.
+        let goal = (0..lines).fold(table.blank_code(blank), |code, line| {
+            let tiles = if line == blank { length - 1 } else { length };
+            code + ((tiles as u64) << table.shift(line, line))
+        });
+        let mut queue = VecDeque::new();
+        table.distances.insert(goal, 0);
+        queue.push_back(goal);
+
+        while let Some(code) = queue.pop_front() {
+            let distance = table.distances[&code];
+            let blank = table.blank(code);
+            let neighbors = [blank.checked_sub(1), Some(blank + 1).filter(|l| *l < lines)];
+
+            for &from in neighbors.iter().flatten() {
+                for goal in 0..lines {
+                    if table.count(code, from, goal) == 0 {
+                        continue;
+                    }
+                    let next = table.moved(code, from, blank, goal);
+
+                    if let Entry::Vacant(entry) = table.distances.entry(next) {
+                        entry.insert(distance + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Packs the lines and goal lines of the tiles, blank excluded.
+    pub fn encode(&self, tiles: impl Iterator<Item = (usize, usize)>, blank: usize) -> u64 {
+        tiles.fold(self.blank_code(blank), |code, (line, goal)| {
+            code + (1 << self.shift(line, goal))
+        })
+    }
+
+    /// The state after the tile of goal line `goal` moves from line `from` to the blank's line
+    /// `to`, the blank taking its place.
+    pub fn moved(&self, code: u64, from: usize, to: usize, goal: usize) -> u64 {
+        code - (1 << self.shift(from, goal)) + (1 << self.shift(to, goal)) - self.blank_code(to)
+            + self.blank_code(from)
+    }
+
+    pub fn distance(&self, code: u64) -> u32 {
+        self.distances.get(&code).copied().unwrap_or(0) as u32
+    }
+
+    fn shift(&self, line: usize, goal: usize) -> u32 {
+        (line * self.lines + goal) as u32 * self.bits
+    }
+
+    fn blank_code(&self, line: usize) -> u64 {
+        (line as u64) << self.shift(self.lines, 0)
+    }
+
+    fn blank(&self, code: u64) -> usize {
+        (code >> self.shift(self.lines, 0)) as usize
+    }
+
+    fn count(&self, code: u64, line: usize, goal: usize) -> u64 {
+        (code >> self.shift(line, goal)) & ((1 << self.bits) - 1)
+    }
+}
+
+/// Additive pattern databases: the tiles are split into groups and, for each group, the fewest
+/// moves of its own tiles needed to bring them home is kept for every placement of them. Each
+/// move moves one tile only, so the sum over the groups never overestimates.
+pub struct PatternDatabase {
+    /// Group of each tile and the weight of its cell in the group's index, `None` for the blank.
+    places: Vec<Option<(usize, usize)>>,
+    tables: Vec<Vec<u8>>,
+}
+
+impl PatternDatabase {
+    /// The databases of the goal, built the first time they are asked for.
+    pub fn get(goal: &Board) -> Result<Arc<PatternDatabase>, Box<dyn Error>> {
+        static CACHE: Cache<PatternDatabase> = OnceLock::new();
+
+        cached(&CACHE, goal, || PatternDatabase::build(goal))
+    }
+
+    fn build(goal: &Board) -> Result<PatternDatabase, Box<dyn Error>> {
+        Heuristic::PatternDatabase.check_size(goal.width(), goal.height())?;
+        let cells = goal.tiles().len();
+
+        // Consecutive tiles in groups as even as possible, 5-5-5 on a 4x4 board.
+        let tiles = cells - 1;
+        let groups = tiles.div_ceil(PATTERN_MAX_TILES);
+        let mut places = vec![None; cells];
+        let mut tables = Vec::new();
+        let mut first = 1;
+
+        for group in 0..groups {
+            let size = tiles / groups + usize::from(group < tiles % groups);
+            let pattern: Vec<usize> = (first..first + size).collect();
+            for (slot, tile) in pattern.iter().enumerate() {
+                places[*tile] = Some((group, cells.pow(slot as u32)));
+            }
+
+            tables.push(pattern_table(goal, &pattern));
+            first += size;
+        }
+
+        Ok(PatternDatabase { places, tables })
+    }
+
+    /// Index of each group's placement for the tiles of a board.
+    pub fn indexes(&self, tiles: &[u16]) -> Vec<usize> {
+        let mut indexes = vec![0; self.tables.len()];
+        for (cell, tile) in tiles.iter().enumerate() {
+            if let Some((group, weight)) = self.places[*tile as usize] {
+                indexes[group] += cell * weight;
+            }
+        }
+
+        indexes
+    }
+
+    /// Updates `indexes` after `tile` moves from cell `from` to cell `to`.
+    pub fn moved(&self, indexes: &mut [usize], tile: u16, from: usize, to: usize) {
+        if let Some((group, weight)) = self.places[tile as usize] {
+            indexes[group] = indexes[group] + to * weight - from * weight;
+        }
+    }
+
+    pub fn distance(&self, indexes: &[usize]) -> u32 {
+        self.tables
+            .iter()
+            .zip(indexes)
+            .map(|(table, index)| table[*index] as u32)
+            .sum()
+    }
+}
+
+/// Fewest moves of the `pattern` tiles to bring them home, for every placement of them. The
+/// search runs over the placements and the blank, moving other tiles being free.
+fn pattern_table(goal: &Board, pattern: &[usize]) -> Vec<u8> {
+    let (width, height) = (goal.width(), goal.height());
+    let cells = width * height;
+    let weights: Vec<usize> = (0..pattern.len())
+        .map(|slot| cells.pow(slot as u32))
+        .collect();
+    let placements = cells.pow(pattern.len() as u32);
+    let mut distances = vec![UNKNOWN; placements * cells];
+    let mut queue = VecDeque::new();
+
+    let home = |tile: usize| {
+        goal.tiles()
+            .iter()
+            .position(|goal_tile| *goal_tile as usize == tile)
+            .unwrap_or(0)
+    };
+    let solved = pattern
+        .iter()
+        .zip(&weights)
+        .map(|(tile, weight)| home(*tile) * weight)
+        .sum::<usize>()
+        * cells
+        + home(0);
+    distances[solved] = 0;
+    queue.push_back(solved);
+
+    while let Some(state) = queue.pop_front() {
+        let distance = distances[state];
+        let (placement, blank) = (state / cells, state % cells);
+        let (column, row) = (blank % width, blank / width);
+        let neighbors = [
+            (column > 0).then(|| blank - 1),
+            (column + 1 < width).then(|| blank + 1),
+            (row > 0).then(|| blank - width),
+            (row + 1 < height).then(|| blank + width),
+        ];
+
+        for &next in neighbors.iter().flatten() {
+            let weight = weights
+                .iter()
+                .find(|weight| placement / **weight % cells == next);
+
+            // Sliding a pattern tile into the blank costs a move, any other tile is free.
+            let (next_state, cost) = match weight {
+                Some(weight) => (
+                    (placement + blank * weight - next * weight) * cells + next,
+                    1,
+                ),
+                None => (placement * cells + next, 0),
+            };
+
+            if distances[next_state] == UNKNOWN || distances[next_state] > distance + cost {
+                distances[next_state] = distance + cost;
+                if cost == 0 {
+                    queue.push_front(next_state);
+                } else {
+                    queue.push_back(next_state);
+                }
+            }
+        }
+    }
+
+    distances
+        .chunks(cells)
+        .map(|blanks| blanks.iter().copied().min().unwrap_or(UNKNOWN))
+        .collect()
+}
+
+fn cached<T>(
+    cache: &Cache<T>,
+    goal: &Board,
+    build: impl FnOnce() -> Result<T, Box<dyn Error>>,
+) -> Result<Arc<T>, Box<dyn Error>> {
+    let mut tables = cache
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .map_err(|_| "Heuristic tables are poisoned")?;
+
+    if let Some(table) = tables.get(goal) {
+        return Ok(Arc::clone(table));
+    }
+
+    let table = Arc::new(build()?);
+    tables.insert(goal.clone(), Arc::clone(&table));
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{move_tile, Operation};
+    use crate::scramble::shuffle_arr;
+    use crate::table::DistanceTable;
+
+    #[test]
+    fn walking_table_should_reach_every_state() -> Result<(), Box<dyn Error>> {
+        // The well-known number of walking distance states of the 4x4 board.
+        let table = WalkingTable::build(4, 4, 3)?;
+        assert_eq!(table.distances.len(), 24_964);
+        assert!(WalkingTable::build(6, 6, 5).is_err());
+
+        assert_eq!(
+            "walking-distance".parse::<Heuristic>()?,
+            Heuristic::WalkingDistance
+        );
+        assert!("euclid".parse::<Heuristic>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn pattern_database_should_never_overestimate() -> Result<(), Box<dyn Error>> {
+        let table = DistanceTable::build(3, 3)?;
+        let database = PatternDatabase::get(&Board::solved(3, 3))?;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..200 {
+            let board = shuffle_arr(&mut rng, 3, 3);
+            let estimate = database.distance(&database.indexes(board.tiles()));
+            assert!(estimate as usize <= table.distance(&board).ok_or("Missing distance")?);
+        }
+
+        // A single group on 2x2 boards, so the database is exact.
+        let database = PatternDatabase::get(&Board::solved(2, 2))?;
+        let board = move_tile(&Board::solved(2, 2), Operation::DOWN);
+        assert_eq!(database.distance(&database.indexes(board.tiles())), 1);
+        assert!(PatternDatabase::get(&Board::solved(5, 4)).is_err());
+
+        Ok(())
+    }
+}
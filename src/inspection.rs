@@ -0,0 +1,227 @@
+//! Inspection before a solve, timed like in speedcubing: the clock of the solve starts with the
+//! first move, and starting after the inspection time costs two seconds, or the solve.
+
+use crate::game::{GameData, GameState};
+use crate::stats::SolveRecord;
+use std::{
+    error::Error,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Inspection time used when none is given.
+pub const DEFAULT_INSPECTION: Duration = Duration::from_secs(15);
+
+/// Starting this late after the inspection time is a +2, any later is a DNF.
+const GRACE: Duration = Duration::from_secs(2);
+
+/// Seconds into the inspection at which the player is warned.
+const WARNINGS: [u64; 2] = [8, 12];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Penalty {
+    PlusTwo,
+    Dnf,
+}
+
+impl fmt::Display for Penalty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Penalty::PlusTwo => write!(f, "+2"),
+            Penalty::Dnf => write!(f, "dnf"),
+        }
+    }
+}
+
+impl FromStr for Penalty {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Penalty, Box<dyn Error>> {
+        match s {
+            "+2" => Ok(Penalty::PlusTwo),
+            "dnf" => Ok(Penalty::Dnf),
+            _ => Err(format!("Invalid penalty '{}', expected +2 or dnf", s).into()),
+        }
+    }
+}
+
+pub struct Inspection {
+    limit: Duration,
+    shown_at: Instant,
+    used: Option<Duration>,
+}
+
+impl Inspection {
+    /// Starts inspecting a scramble shown just now.
+    pub fn new(limit: Duration) -> Inspection {
+        Inspection {
+            limit,
+            shown_at: Instant::now(),
+            used: None,
+        }
+    }
+
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    /// Time spent inspecting, `None` until the solve started.
+    pub fn used(&self) -> Option<Duration> {
+        self.used
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.used.unwrap_or_else(|| self.shown_at.elapsed())
+    }
+
+    /// The last warning reached, in seconds into the inspection.
+    pub fn warning(&self) -> Option<u64> {
+        let elapsed = self.elapsed();
+
+        WARNINGS.iter().copied().rev().find(|seconds| {
+            let at = Duration::from_secs(*seconds);
+
+            at < self.limit && at <= elapsed
+        })
+    }
+
+    pub fn penalty(&self) -> Option<Penalty> {
+        let elapsed = self.elapsed();
+
+        if elapsed > self.limit + GRACE {
+            Some(Penalty::Dnf)
+        } else if elapsed > self.limit {
+            Some(Penalty::PlusTwo)
+        } else {
+            None
+        }
+    }
+
+    /// Ends the inspection once the solve started with a move, or as a DNF when it ran too
+    /// long. A DNF ends the game as `TIME_OVER`. Returns whether the inspection just ended.
+    pub fn check(&mut self, game_data: &mut GameData) -> bool {
+        if self.used.is_some() {
+            return false;
+        }
+
+        if game_data.game_state != GameState::INIT {
+            // The solve clock started with the first move, inspecting ended just before.
+            self.used = Some(
+                self.shown_at
+                    .elapsed()
+                    .saturating_sub(game_data.elapsed_time()),
+            );
+        } else if self.shown_at.elapsed() > self.limit + GRACE {
+            self.used = Some(self.shown_at.elapsed());
+        } else {
+            return false;
+        }
+
+        if self.penalty() == Some(Penalty::Dnf) {
+            game_data.base_time = game_data.elapsed_time();
+            game_data.game_state = GameState::TIME_OVER;
+        }
+
+        true
+    }
+
+    /// Adds the inspection and its penalty to the result of the solve, a +2 is added to its time.
+    pub fn apply(&self, record: SolveRecord) -> SolveRecord {
+        let penalty = self.penalty();
+        let time = match penalty {
+            Some(Penalty::PlusTwo) => record.time + GRACE,
+            _ => record.time,
+        };
+
+        SolveRecord {
+            inspection_time: self.used,
+            penalty,
+            time,
+            dnf: record.dnf || penalty == Some(Penalty::Dnf),
+            ..record
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Operation;
+    use crate::game::handle_move_operation;
+    use crate::scramble::Difficulty;
+
+    fn inspection(limit: Duration, inspected: Duration) -> Inspection {
+        Inspection {
+            limit,
+            shown_at: Instant::now() - inspected,
+            used: None,
+        }
+    }
+
+    fn game() -> Result<GameData, Box<dyn Error>> {
+        Ok(GameData::from_scramble(
+            "1,2,3/4,5,6/0,7,8".parse()?,
+            Difficulty::Easy,
+            None,
+        ))
+    }
+
+    #[test]
+    fn inspection_should_warn_and_penalize_late_starts() -> Result<(), Box<dyn Error>> {
+        let limit = DEFAULT_INSPECTION;
+
+        let early = inspection(limit, Duration::from_secs(5));
+        assert_eq!(early.warning(), None);
+        assert_eq!(early.penalty(), None);
+
+        let late = inspection(limit, Duration::from_millis(12_500));
+        assert_eq!(late.warning(), Some(12));
+        assert_eq!(late.penalty(), None);
+
+        let mut game_data = game()?;
+        let mut plus_two = inspection(limit, Duration::from_secs(16));
+        assert!(!plus_two.check(&mut game_data));
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        assert!(plus_two.check(&mut game_data));
+        assert_eq!(plus_two.penalty(), Some(Penalty::PlusTwo));
+        assert!(game_data.game_state == GameState::PLAYING);
+
+        let solve = SolveRecord::new(
+            game_data.scramble.clone(),
+            Vec::new(),
+            Duration::from_secs(10),
+        );
+        let record = plus_two.apply(solve);
+        assert_eq!(record.time, Duration::from_secs(12));
+        assert_eq!(record.penalty, Some(Penalty::PlusTwo));
+
+        // Warnings past a short inspection are never called.
+        assert_eq!(
+            inspection(Duration::from_secs(10), Duration::from_secs(11)).warning(),
+            Some(8)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn inspection_should_end_game_as_dnf() -> Result<(), Box<dyn Error>> {
+        let mut game_data = game()?;
+        let mut dnf = inspection(DEFAULT_INSPECTION, Duration::from_secs(18));
+
+        assert!(dnf.check(&mut game_data));
+        assert!(game_data.game_state == GameState::TIME_OVER);
+
+        let record = dnf.apply(SolveRecord::from_game(&game_data));
+        assert!(record.dnf);
+        assert_eq!(record.penalty, Some(Penalty::Dnf));
+        assert_eq!("dnf".parse::<Penalty>()?, Penalty::Dnf);
+        assert_eq!(
+            Penalty::PlusTwo.to_string().parse::<Penalty>()?,
+            Penalty::PlusTwo
+        );
+
+        Ok(())
+    }
+}
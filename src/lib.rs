@@ -0,0 +1,49 @@
+//! The puzzle engine behind `rust-15-puzzle-cli`: board representation, moves, solvability
+//! checks, scrambling and an optimal solver for boards of any size.
+//!
+//! ```
+//! use rust_15_puzzle_cli::{is_done, is_solvable, move_tile, solve, Board, Operation};
+//!
+//! let board: Board = "1,2,3/4,5,6/0,7,8".parse().unwrap();
+//! assert!(is_solvable(&board));
+//!
+//! let solution = solve(&board).unwrap();
+//! assert_eq!(solution.moves, vec![Operation::LEFT, Operation::LEFT]);
+//!
+//! let solved = solution
+//!     .moves
+//!     .iter()
+//!     .fold(board, |board, operation| move_tile(&board, *operation));
+//! assert!(is_done(&solved));
+//! ```
+
+pub mod analysis;
+pub mod blindfold;
+pub mod board;
+pub mod daily;
+pub mod drill;
+pub mod game;
+pub mod ghost;
+pub mod goal;
+pub mod heuristic;
+pub mod inspection;
+pub mod marathon;
+pub mod method;
+pub mod net;
+pub mod reduction;
+pub mod scramble;
+pub mod solver;
+pub mod stats;
+pub mod table;
+pub mod timing;
+pub mod tutorial;
+
+pub use board::{
+    count_inversion, format_moves, is_done, is_solvable, is_solvable_to, manhattan_distance,
+    move_tile, parse_moves, parse_size, target_index, Board, Operation,
+};
+pub use scramble::{
+    random_walk, random_walk_from, scramble_board, scramble_to, shuffle_arr, shuffle_to,
+    Difficulty, Scramble,
+};
+pub use solver::{solve, solve_cancellable, solve_to, solve_with, Progress, Solution};
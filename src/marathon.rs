@@ -0,0 +1,251 @@
+//! Marathons: several scrambles solved in a row on one clock, either a fixed number of them or as
+//! many as possible within a time budget, the time attack.
+
+use crate::board::{Board, Operation};
+use crate::game::{GameData, GameMode, GameState};
+use crate::scramble::Difficulty;
+use crate::stats::SolveRecord;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Puzzles(usize),
+    Time(Duration),
+}
+
+pub struct Marathon {
+    limit: Limit,
+    time_over: bool,
+    scramble: Option<Board>,
+    difficulty: Option<Difficulty>,
+    moves: Vec<Operation>,
+    move_times: Vec<Duration>,
+    splits: Vec<Duration>,
+    pauses: usize,
+}
+
+impl Marathon {
+    pub fn new(puzzles: usize) -> Marathon {
+        Marathon::with_limit(Limit::Puzzles(puzzles))
+    }
+
+    pub fn time_attack(budget: Duration) -> Marathon {
+        Marathon::with_limit(Limit::Time(budget))
+    }
+
+    fn with_limit(limit: Limit) -> Marathon {
+        Marathon {
+            limit,
+            time_over: false,
+            scramble: None,
+            difficulty: None,
+            moves: Vec::new(),
+            move_times: Vec::new(),
+            splits: Vec::new(),
+            pauses: 0,
+        }
+    }
+
+    pub fn limit(&self) -> Limit {
+        self.limit
+    }
+
+    pub fn mode(&self) -> GameMode {
+        match self.limit {
+            Limit::Puzzles(puzzles) => GameMode::Marathon(puzzles),
+            Limit::Time(budget) => GameMode::TimeAttack(budget),
+        }
+    }
+
+    /// Number of solved puzzles, the score of a time attack.
+    pub fn solved(&self) -> usize {
+        self.splits.len()
+    }
+
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.splits.iter().sum()
+    }
+
+    pub fn finished(&self) -> bool {
+        match self.limit {
+            Limit::Puzzles(puzzles) => self.splits.len() >= puzzles,
+            Limit::Time(_) => self.time_over,
+        }
+    }
+
+    /// Prepares the game of the next puzzle. The first one waits for a move like any game, the
+    /// following ones keep the clock running from the total so far.
+    pub fn start(&mut self, game_data: &mut GameData) {
+        game_data.mode = self.mode();
+        game_data.ranked = false;
+        self.scramble
+            .get_or_insert_with(|| game_data.scramble.clone());
+
+        if !self.splits.is_empty() {
+            game_data.base_time = self.total_time();
+            game_data.start_time = Instant::now();
+            game_data.game_state = GameState::PLAYING;
+        }
+    }
+
+    /// Adds a solved puzzle. Its clock includes the earlier puzzles, as set up by `start`.
+    pub fn add_solve(&mut self, game_data: &GameData) {
+        self.difficulty = Some(game_data.difficulty);
+
+        self.splits.push(game_data.base_time - self.total_time());
+        self.moves.extend(game_data.moves.iter());
+        self.move_times.extend(game_data.move_times.iter());
+        self.pauses += game_data.pauses;
+    }
+
+    /// Ends a time attack once its budget is used up, also when the last solve came too late.
+    /// Returns whether it just ended.
+    pub fn check_time(&mut self, game_data: &mut GameData) -> bool {
+        let budget = match self.limit {
+            Limit::Time(budget) if !self.time_over => budget,
+            _ => return false,
+        };
+
+        if game_data.elapsed_time() < budget {
+            return false;
+        }
+
+        game_data.base_time = budget;
+        game_data.game_state = GameState::TIME_OVER;
+        self.time_over = true;
+        self.pauses += game_data.pauses;
+
+        true
+    }
+
+    /// The whole series as one result, `None` until it started.
+    pub fn to_record(&self) -> Option<SolveRecord> {
+        let scramble = self.scramble.clone()?;
+        let time = match self.limit {
+            Limit::Time(budget) if self.time_over => budget,
+            _ => self.total_time(),
+        };
+
+        Some(SolveRecord {
+            difficulty: self.difficulty,
+            move_times: self.move_times.clone(),
+            mode: self.mode(),
+            splits: self.splits.clone(),
+            pauses: self.pauses,
+            ..SolveRecord::new(scramble, self.moves.clone(), time)
+        })
+    }
+}
+
+/// Most puzzles solved in a time attack of `budget` on boards of `size`.
+pub fn time_attack_high_score(
+    records: &[SolveRecord],
+    size: (usize, usize),
+    budget: Duration,
+) -> Option<usize> {
+    records
+        .iter()
+        .filter(|record| record.mode == GameMode::TimeAttack(budget) && record.size() == size)
+        .map(|record| record.splits.len())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::parse_moves;
+    use crate::game::handle_move_operation;
+    use std::error::Error;
+
+    fn solve(marathon: &mut Marathon, scramble: &str, moves: &str, time_ms: u64) {
+        let mut game_data =
+            GameData::from_scramble(scramble.parse().unwrap(), Difficulty::Easy, None);
+        marathon.start(&mut game_data);
+        assert_eq!(game_data.base_time, marathon.total_time());
+
+        for operation in parse_moves(moves).unwrap() {
+            handle_move_operation(&mut game_data, operation);
+        }
+        assert!(game_data.game_state == GameState::DONE);
+
+        game_data.base_time = marathon.total_time() + Duration::from_millis(time_ms);
+        marathon.add_solve(&game_data);
+    }
+
+    #[test]
+    fn marathon_should_keep_splits_and_total() -> Result<(), Box<dyn Error>> {
+        let mut marathon = Marathon::new(2);
+        assert!(marathon.to_record().is_none());
+        assert!(!marathon.check_time(&mut GameData::from_scramble(
+            Board::solved(3, 3),
+            Difficulty::Easy,
+            None
+        )));
+
+        solve(&mut marathon, "1,2,3/4,5,6/0,7,8", "aa", 1500);
+        assert!(!marathon.finished());
+        // The clock already runs on later puzzles, so one move is enough to finish.
+        solve(&mut marathon, "1,2,3/4,5,0/7,8,6", "w", 700);
+        assert!(marathon.finished());
+
+        assert_eq!(
+            marathon.splits(),
+            &[Duration::from_millis(1500), Duration::from_millis(700)]
+        );
+
+        let record = marathon.to_record().unwrap();
+        assert_eq!(record.time, Duration::from_millis(2200));
+        assert_eq!(record.scramble, "1,2,3/4,5,6/0,7,8".parse()?);
+        assert_eq!(record.moves, parse_moves("aaw")?);
+        assert_eq!(record.mode, GameMode::Marathon(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn time_attack_should_end_when_budget_is_used() -> Result<(), Box<dyn Error>> {
+        let mut time_attack = Marathon::time_attack(Duration::from_secs(60));
+        solve(&mut time_attack, "1,2,3/4,5,6/0,7,8", "aa", 20_000);
+        assert!(!time_attack.finished());
+
+        let mut game_data =
+            GameData::from_scramble("1,2,3/4,5,6/0,7,8".parse()?, Difficulty::Easy, None);
+        time_attack.start(&mut game_data);
+        assert!(!time_attack.check_time(&mut game_data));
+
+        // A solve that comes in after the budget doesn't count.
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        handle_move_operation(&mut game_data, Operation::LEFT);
+        game_data.base_time = Duration::from_secs(61);
+        assert!(time_attack.check_time(&mut game_data));
+        assert!(game_data.game_state == GameState::TIME_OVER);
+        assert!(time_attack.finished());
+        assert!(!time_attack.check_time(&mut game_data));
+
+        let record = time_attack.to_record().unwrap();
+        assert_eq!(record.time, Duration::from_secs(60));
+        assert_eq!(record.splits.len(), 1);
+
+        let records = vec![
+            record.clone(),
+            SolveRecord {
+                splits: vec![Duration::from_secs(10); 4],
+                ..record.clone()
+            },
+            SolveRecord {
+                splits: vec![Duration::from_secs(10); 5],
+                mode: GameMode::TimeAttack(Duration::from_secs(120)),
+                ..record
+            },
+        ];
+        let budget = Duration::from_secs(60);
+        assert_eq!(time_attack_high_score(&records, (3, 3), budget), Some(4));
+        assert_eq!(time_attack_high_score(&records, (4, 4), budget), None);
+
+        Ok(())
+    }
+}
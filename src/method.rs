@@ -0,0 +1,127 @@
+//! The row-by-row method: the top row and the left column of the unsolved part are solved one
+//! after the other, whichever is longer first, until a 2x2 block is left.
+
+use crate::board::Board;
+
+/// What a stage solves, which decides how it is solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageKind {
+    /// The top row of the unsolved part.
+    Row,
+    /// The left column of the unsolved part.
+    Column,
+    /// The block of at most 2x2 left at the end.
+    Final,
+}
+
+/// A part of the board solved as one step of the method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stage {
+    pub name: String,
+    pub kind: StageKind,
+    /// Indices of the cells solved in this stage, their tiles are `index + 1`.
+    pub cells: Vec<usize>,
+}
+
+impl Stage {
+    pub fn is_solved(&self, board: &Board) -> bool {
+        self.cells
+            .iter()
+            .all(|index| board.tiles()[*index] as usize == index + 1)
+    }
+
+    /// The tiles that belong in this stage.
+    pub fn tiles(&self) -> Vec<u16> {
+        self.cells.iter().map(|index| *index as u16 + 1).collect()
+    }
+}
+
+/// The stages of solving a board of `width` x `height`, in order.
+pub fn stages(width: usize, height: usize) -> Vec<Stage> {
+    let (mut top, mut left) = (0, 0);
+    let mut stages = Vec::new();
+
+    loop {
+        let rows = height - top;
+        let columns = width - left;
+
+        if rows <= 2 && columns <= 2 {
+            // The blank ends up in the last cell, so it isn't part of the final stage.
+            let cells = (top..height)
+                .flat_map(|row| (left..width).map(move |column| row * width + column))
+                .filter(|index| index + 1 != width * height)
+                .collect();
+            stages.push(Stage {
+                name: format!("final {}x{}", columns, rows),
+                kind: StageKind::Final,
+                cells,
+            });
+
+            return stages;
+        }
+
+        if rows > 2 && (rows >= columns || columns <= 2) {
+            stages.push(Stage {
+                name: format!("row {}", top + 1),
+                kind: StageKind::Row,
+                cells: (left..width).map(|column| top * width + column).collect(),
+            });
+            top += 1;
+        } else {
+            stages.push(Stage {
+                name: format!("column {}", left + 1),
+                kind: StageKind::Column,
+                cells: (top..height).map(|row| row * width + left).collect(),
+            });
+            left += 1;
+        }
+    }
+}
+
+/// Number of stages solved in order from the first, the index of the stage being worked on.
+pub fn progress(stages: &[Stage], board: &Board) -> usize {
+    stages
+        .iter()
+        .take_while(|stage| stage.is_solved(board))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn stages_should_alternate_rows_and_columns() {
+        let names: Vec<String> = stages(4, 4).into_iter().map(|stage| stage.name).collect();
+        assert_eq!(
+            names,
+            vec!["row 1", "column 1", "row 2", "column 2", "final 2x2"]
+        );
+
+        let stages_3x3 = stages(3, 3);
+        let kinds: Vec<StageKind> = stages_3x3.iter().map(|stage| stage.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![StageKind::Row, StageKind::Column, StageKind::Final]
+        );
+        assert_eq!(stages_3x3[0].cells, vec![0, 1, 2]);
+        assert_eq!(stages_3x3[1].cells, vec![3, 6]);
+        assert_eq!(stages_3x3[2].cells, vec![4, 5, 7]);
+
+        let names: Vec<String> = stages(5, 2).into_iter().map(|stage| stage.name).collect();
+        assert_eq!(names, vec!["column 1", "column 2", "column 3", "final 2x2"]);
+    }
+
+    #[test]
+    fn progress_should_count_solved_stages_in_order() -> Result<(), Box<dyn Error>> {
+        let stages = stages(3, 3);
+
+        assert_eq!(progress(&stages, &"1,2,3/4,5,6/7,0,8".parse()?), 2);
+        // The first column is solved, but not the first row before it.
+        assert_eq!(progress(&stages, &"2,1,3/4,5,6/7,8,0".parse()?), 0);
+        assert_eq!(progress(&stages, &Board::solved(3, 3)), 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+//! Line based protocol of networked races.
+//!
+//! The host sends `new <board> <difficulty>` for every scramble, then both sides send
+//! `move <key> <ms>` for each of their moves, `<ms>` being their elapsed time. The joining side
+//! sends `rematch <difficulty>` to ask the host for a new scramble and either side sends `bye`
+//! before leaving.
+//!
+//! Spectators get the same `new` and `move` messages from a [`Broadcaster`], plus `pause <ms>`
+//! and `resume` when the player pauses.
+
+use crate::board::{Board, Operation};
+use crate::scramble::Difficulty;
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Port used when an address is given without one.
+pub const DEFAULT_PORT: u16 = 7878;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    New(Board, Difficulty),
+    Move(Operation, Duration),
+    Rematch(Difficulty),
+    Pause(Duration),
+    Resume,
+    Bye,
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::New(board, difficulty) => write!(f, "new {} {}", board, difficulty),
+            Message::Move(operation, time) => {
+                write!(f, "move {} {}", operation.to_char(), time.as_millis())
+            }
+            Message::Rematch(difficulty) => write!(f, "rematch {}", difficulty),
+            Message::Pause(time) => write!(f, "pause {}", time.as_millis()),
+            Message::Resume => write!(f, "resume"),
+            Message::Bye => write!(f, "bye"),
+        }
+    }
+}
+
+impl FromStr for Message {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+
+        let message = match (parts.next(), parts.next(), parts.next()) {
+            (Some("new"), Some(board), Some(difficulty)) if parts.next().is_none() => {
+                Message::New(board.parse()?, difficulty.parse()?)
+            }
+            (Some("move"), Some(key), Some(time)) if parts.next().is_none() => {
+                let mut chars = key.chars();
+                let operation = match (chars.next().and_then(Operation::from_char), chars.next()) {
+                    (Some(operation), None) => operation,
+                    _ => return Err(format!("Invalid move: {}", key).into()),
+                };
+
+                Message::Move(operation, Duration::from_millis(time.parse()?))
+            }
+            (Some("rematch"), Some(difficulty), None) => Message::Rematch(difficulty.parse()?),
+            (Some("pause"), Some(time), None) => {
+                Message::Pause(Duration::from_millis(time.parse()?))
+            }
+            (Some("resume"), None, None) => Message::Resume,
+            (Some("bye"), None, None) => Message::Bye,
+            _ => return Err(format!("Invalid message: {}", s).into()),
+        };
+
+        Ok(message)
+    }
+}
+
+/// Appends the default port to addresses without one, `localhost` becomes `localhost:7878`.
+pub fn with_default_port(address: &str) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    }
+}
+
+pub fn send(stream: &mut TcpStream, message: &Message) -> io::Result<()> {
+    writeln!(stream, "{}", message)?;
+    stream.flush()
+}
+
+/// Reads the messages sent by the other side, one per line.
+pub struct MessageReader {
+    reader: BufReader<TcpStream>,
+}
+
+impl MessageReader {
+    pub fn new(stream: TcpStream) -> MessageReader {
+        MessageReader {
+            reader: BufReader::new(stream),
+        }
+    }
+
+    /// The next message, `None` once the connection is closed.
+    pub fn read(&mut self) -> Result<Option<Message>, Box<dyn Error>> {
+        let mut line = String::new();
+
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(line.trim().parse()?))
+    }
+}
+
+/// Publishes a game to any number of spectators. Spectators joining late first get everything
+/// sent since the last `New`, so they can replay the game up to now.
+pub struct Broadcaster {
+    audience: Arc<Mutex<Audience>>,
+    address: SocketAddr,
+}
+
+#[derive(Default)]
+struct Audience {
+    spectators: Vec<TcpStream>,
+    history: Vec<Message>,
+}
+
+impl Broadcaster {
+    pub fn bind(address: &str) -> io::Result<Broadcaster> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let audience = Arc::new(Mutex::new(Audience::default()));
+
+        let shared = audience.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                // A spectator that stops reading must not freeze the game.
+                let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+                let _ = stream.set_nodelay(true);
+
+                let mut audience = shared.lock().unwrap();
+                if audience
+                    .history
+                    .iter()
+                    .all(|message| send(&mut stream, message).is_ok())
+                {
+                    audience.spectators.push(stream);
+                }
+            }
+        });
+
+        Ok(Broadcaster { audience, address })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Sends `message` to every spectator, dropping the ones that went away.
+    pub fn send(&self, message: &Message) {
+        let mut audience = self.audience.lock().unwrap();
+
+        if let Message::New(..) = message {
+            audience.history.clear();
+        }
+        audience.history.push(message.clone());
+        audience
+            .spectators
+            .retain_mut(|stream| send(stream, message).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn message_should_round_trip() -> Result<(), Box<dyn Error>> {
+        let messages = vec![
+            Message::New("1,2,3/4,5,6/7,0,8".parse()?, Difficulty::Hard),
+            Message::Move(Operation::LEFT, Duration::from_millis(1234)),
+            Message::Rematch(Difficulty::Easy),
+            Message::Pause(Duration::from_millis(5000)),
+            Message::Resume,
+            Message::Bye,
+        ];
+
+        for message in messages {
+            assert_eq!(message.to_string().parse::<Message>()?, message);
+        }
+
+        assert_eq!(
+            "move d 10".parse::<Message>()?,
+            Message::Move(Operation::RIGHT, Duration::from_millis(10))
+        );
+        assert!("move x 10".parse::<Message>().is_err());
+        assert!("move dd 10".parse::<Message>().is_err());
+        assert!("move d".parse::<Message>().is_err());
+        assert!("new 1,2/3,4 easy".parse::<Message>().is_err());
+        assert!("new 1,2/3,0".parse::<Message>().is_err());
+        assert!("rematch".parse::<Message>().is_err());
+        assert!("hello".parse::<Message>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_default_port_should_keep_given_port() {
+        assert_eq!(with_default_port("localhost"), "localhost:7878");
+        assert_eq!(with_default_port("127.0.0.1:9000"), "127.0.0.1:9000");
+        assert_eq!(with_default_port("0.0.0.0"), "0.0.0.0:7878");
+    }
+
+    #[test]
+    fn messages_should_cross_tcp_connection() -> Result<(), Box<dyn Error>> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+
+        let host = thread::spawn(move || -> Result<Vec<Message>, String> {
+            let (mut stream, _) = listener.accept().map_err(|e| e.to_string())?;
+            let board = "1,2,3/4,5,6/7,0,8"
+                .parse()
+                .map_err(|e: Box<dyn Error>| e.to_string())?;
+            send(&mut stream, &Message::New(board, Difficulty::Easy)).map_err(|e| e.to_string())?;
+
+            let mut reader = MessageReader::new(stream);
+            let mut received = Vec::new();
+            while let Some(message) = reader.read().map_err(|e| e.to_string())? {
+                received.push(message);
+            }
+
+            Ok(received)
+        });
+
+        let mut stream = TcpStream::connect(address)?;
+        let mut reader = MessageReader::new(stream.try_clone()?);
+        assert_eq!(
+            reader.read()?,
+            Some(Message::New("1,2,3/4,5,6/7,0,8".parse()?, Difficulty::Easy))
+        );
+
+        send(
+            &mut stream,
+            &Message::Move(Operation::LEFT, Duration::from_millis(500)),
+        )?;
+        send(&mut stream, &Message::Bye)?;
+        drop(stream);
+        drop(reader);
+
+        assert_eq!(
+            host.join().unwrap()?,
+            vec![
+                Message::Move(Operation::LEFT, Duration::from_millis(500)),
+                Message::Bye
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn broadcaster_should_replay_current_game_to_late_spectators() -> Result<(), Box<dyn Error>> {
+        let broadcaster = Broadcaster::bind("127.0.0.1:0")?;
+        broadcaster.send(&Message::New(
+            "1,2,3/4,5,6/0,7,8".parse()?,
+            Difficulty::Easy,
+        ));
+        broadcaster.send(&Message::New(
+            "1,2,3/4,5,6/7,0,8".parse()?,
+            Difficulty::Easy,
+        ));
+        broadcaster.send(&Message::Move(Operation::LEFT, Duration::from_millis(10)));
+
+        let mut late = MessageReader::new(TcpStream::connect(broadcaster.local_addr())?);
+        assert_eq!(
+            late.read()?,
+            Some(Message::New("1,2,3/4,5,6/7,0,8".parse()?, Difficulty::Easy))
+        );
+        assert_eq!(
+            late.read()?,
+            Some(Message::Move(Operation::LEFT, Duration::from_millis(10)))
+        );
+
+        // Registered yet or not, the spectator gets later messages exactly once.
+        broadcaster.send(&Message::Pause(Duration::from_millis(20)));
+        assert_eq!(
+            late.read()?,
+            Some(Message::Pause(Duration::from_millis(20)))
+        );
+
+        Ok(())
+    }
+}
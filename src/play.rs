@@ -0,0 +1,1246 @@
+use crate::cli::invalid_input;
+use crate::helper::{
+    self, draw_analysis, draw_game, draw_ghost, draw_goal, draw_header, draw_message, tile_length,
+    Analyzer, Event, Events, OptimalSolver, SolverEvent, ThemeMode, ThemeSystem,
+};
+use rand::rngs::ThreadRng;
+use rust_15_puzzle_cli::{
+    analysis::ANALYSIS_MAX_CELLS,
+    blindfold::Blindfold,
+    daily,
+    drill::Drill,
+    game::{
+        finish_blindfold, handle_move_operation, handle_pause, replay_move, GameData, GameMode,
+        GameState,
+    },
+    ghost::Ghost,
+    goal::Goal,
+    inspection::{Inspection, Penalty},
+    marathon::{time_attack_high_score, Limit, Marathon},
+    net::{self, Broadcaster, Message, MessageReader},
+    scramble::{Difficulty, OPTIMAL_SCRAMBLE_MAX_CELLS},
+    stats::{append_record, load_records, SolveRecord},
+    table::{DistanceTable, TABLE_MAX_CELLS},
+    tutorial::Tutorial,
+    Board, Operation, Progress,
+};
+use std::{
+    error::Error,
+    io,
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+use termion::{event::Key, raw::IntoRawMode, screen::AlternateScreen};
+use tui::{
+    backend::TermionBackend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders},
+    Terminal,
+};
+
+pub struct PlayOptions {
+    pub width: usize,
+    pub height: usize,
+    pub difficulty: Difficulty,
+    /// Play the daily puzzle instead of random scrambles.
+    pub daily: bool,
+    /// Two players race on the same scramble, the first on w,a,s,d and the second on arrows.
+    pub race: bool,
+    /// Race against another player over the network.
+    pub network: Option<Network>,
+    /// Stream the game to spectators connecting on this address.
+    pub spectate: Option<String>,
+    /// Replay the best recorded solve next to the game.
+    pub ghost: bool,
+    /// Solve this many puzzles in a row on one clock.
+    pub marathon: Option<usize>,
+    /// Solve as many puzzles as possible within this time.
+    pub time_attack: Option<Duration>,
+    /// Solve within the optimal solution length plus this many moves.
+    pub challenge: Option<usize>,
+    /// Memorize the scramble for at most this long, then solve it with the tiles hidden.
+    pub blindfold: Option<Duration>,
+    /// Inspect the scramble for this long before solving, starting later is penalized.
+    pub inspection: Option<Duration>,
+    /// Walk through the row-by-row method, stage by stage.
+    pub tutorial: bool,
+    /// Practice part of the board with positions of this drill.
+    pub drill: Option<Drill>,
+    /// The layout boards are solved into.
+    pub goal: Goal,
+}
+
+pub enum Network {
+    /// Wait for an opponent on this address and serve the scrambles.
+    Host(String),
+    /// Connect to a host on this address.
+    Join(String),
+}
+
+/// The other end of a networked race.
+struct Peer {
+    stream: TcpStream,
+    host: bool,
+    status: String,
+}
+
+pub fn play(options: PlayOptions) -> Result<(), Box<dyn Error>> {
+    let mut rng = rand::thread_rng();
+
+    check_options(&options).map_err(invalid_input)?;
+
+    // Connect before switching the terminal, so waiting messages and errors stay readable.
+    let (connection, mut players) = match &options.network {
+        Some(network) => {
+            let (mut peer, mut reader) = connect(network)?;
+            let players = if peer.host {
+                let players = new_game(&mut rng, &options, options.difficulty)?;
+                send_message(&mut peer, &new_message(&players[0]));
+                players
+            } else {
+                match reader.read()? {
+                    Some(Message::New(board, difficulty)) => {
+                        race_players(GameData::from_scramble(board, difficulty, None))
+                    }
+                    _ => return Err("The host left before the race started".into()),
+                }
+            };
+
+            (Some((peer, reader)), players)
+        }
+        None => (None, new_game(&mut rng, &options, options.difficulty)?),
+    };
+
+    let broadcaster = match &options.spectate {
+        Some(address) => Some(Broadcaster::bind(&net::with_default_port(address))?),
+        None => None,
+    };
+    let spectate_status = broadcaster.as_ref().map(|broadcaster| {
+        format!(
+            "Streaming to spectators, run `watch {}` to follow",
+            broadcaster.local_addr()
+        )
+    });
+    if let Some(broadcaster) = &broadcaster {
+        broadcaster.send(&new_message(&players[0]));
+    }
+
+    // Terminal initialization
+    let stdout = io::stdout().into_raw_mode()?;
+    let stdout = AlternateScreen::from(stdout);
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.hide_cursor()?;
+
+    // Setup event handlers
+    let events = Events::new();
+    let mut peer = connection.map(|(peer, reader)| {
+        events.forward_messages(reader);
+        peer
+    });
+
+    let mut optimal_solver = OptimalSolver::new(events.sender());
+    let mut analyzer = Analyzer::new();
+
+    request_optimal_length(&mut optimal_solver, &players[0]);
+    let mut status = game_status(&players[0])?;
+    let mut ghost = new_ghost(&options, &players[0])?;
+    let mut marathon = new_marathon(&options);
+    if let Some(marathon) = marathon.as_mut() {
+        marathon.start(&mut players[0]);
+        status = marathon_status(marathon);
+    }
+    let mut blindfold = new_blindfold(&options, &mut players[0]);
+    let mut inspection = options.inspection.map(Inspection::new);
+    let mut tutorial = new_tutorial(&options);
+    if let Some(tutorial) = &tutorial {
+        status = tutorial_status(tutorial, &players[0]);
+    }
+    let mut score: Option<Vec<String>> = None;
+    // How far the search for the optimal length of a scramble got.
+    let mut solving: Option<(Board, Progress)> = None;
+    // Whether the solve is being analyzed, and the analysis once done.
+    let mut analyzing = false;
+    let mut analysis = None;
+    // Perfect hints from the distance table of the size, loaded on first use.
+    let mut hints = false;
+    let mut table = None;
+    let mut winner: Option<usize> = None;
+    let mut theme_system = ThemeSystem::new(ThemeMode::DarkMode);
+
+    loop {
+        if let Some(marathon) = marathon.as_mut() {
+            if marathon.check_time(&mut players[0]) {
+                score = Some(time_attack_score(marathon, &players[0])?);
+                if let Some(record) = marathon.to_record() {
+                    append_record(&record)?;
+                }
+
+                status = marathon_status(marathon);
+            }
+
+            // Only a puzzle that was just solved is still DONE before the marathon is over.
+            if players[0].game_state == GameState::DONE && !marathon.finished() {
+                marathon.add_solve(&players[0]);
+
+                if marathon.finished() {
+                    if let Some(record) = marathon.to_record() {
+                        append_record(&record)?;
+                    }
+                } else {
+                    players = new_game(&mut rng, &options, players[0].difficulty)?;
+                    marathon.start(&mut players[0]);
+                    request_optimal_length(&mut optimal_solver, &players[0]);
+
+                    if let Some(broadcaster) = &broadcaster {
+                        broadcaster.send(&new_message(&players[0]));
+                    }
+                }
+
+                status = marathon_status(marathon);
+            }
+        }
+
+        if let Some(blindfold) = blindfold.as_mut() {
+            blindfold.check_memo(&mut players[0]);
+            status = blindfold_status(blindfold, &players[0]);
+        }
+
+        if let Some(inspection) = inspection.as_mut() {
+            let ended = inspection.check(&mut players[0]);
+
+            if ended && players[0].game_state == GameState::TIME_OVER && players[0].ranked {
+                append_record(&solve_record(&players[0], Some(inspection)))?;
+            }
+            if ended || inspection.used().is_none() {
+                status = inspection_status(inspection, &players[0])?;
+            }
+        }
+
+        if hints {
+            status = hint_status(&mut table, &players[0])?;
+        }
+
+        if let Some(result) = analyzer.poll(&players[0].scramble) {
+            analysis = Some(result);
+        }
+
+        let length = tile_length(&players[0].arr_state);
+        let board_width = (players[0].arr_state.width() as u16 * (length + 3) + 2).max(48);
+        let board_height = players[0].arr_state.height() as u16 * length + 3;
+        let banner = winner
+            .map(|winner| race_banner(&options, &players, winner))
+            .or_else(|| marathon.as_ref().and_then(marathon_banner))
+            .or_else(|| challenge_banner(&players[0]))
+            .or_else(|| drill_banner(&players[0]))
+            .or_else(|| {
+                blindfold
+                    .as_ref()
+                    .and_then(|_| blindfold_banner(&players[0]))
+            })
+            .or_else(|| {
+                inspection
+                    .as_ref()
+                    .and_then(|inspection| inspection_banner(inspection, &players[0]))
+            });
+        let solving_status = solving
+            .as_ref()
+            .filter(|(board, _)| *board == players[0].scramble)
+            .map(|(_, progress)| solving_status(progress));
+        let header_status = match (&peer, solving_status) {
+            (Some(peer), _) => peer.status.clone(),
+            // The move limit of a challenge waits for the search, show how far it got.
+            (None, Some(solving_status)) if options.challenge.is_some() || status.is_empty() => {
+                solving_status
+            }
+            (None, _) if !status.is_empty() => status.clone(),
+            (None, _) => spectate_status
+                .clone()
+                .unwrap_or_else(|| ghost_status(&options, ghost.as_ref())),
+        };
+        // Other goals are shown next to the boards, the usual one goes without saying.
+        let goal_preview = Some(&options.goal).filter(|goal| **goal != Goal::Standard);
+        let boards = players.len() + ghost.iter().count() + goal_preview.iter().count();
+
+        terminal.draw(|mut f| {
+            let layout_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Length(4),
+                        Constraint::Min(0), // main render
+                    ]
+                    .as_ref(),
+                )
+                .split(f.size());
+
+            let mut constraints = vec![Constraint::Length(10)];
+            constraints.extend((0..boards).map(|_| Constraint::Length(board_width)));
+            constraints.push(Constraint::Min(0));
+
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(constraints.as_slice())
+                .split(layout_chunks[1]);
+
+            let footer_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(board_height), Constraint::Min(0)].as_ref())
+                .split(chunks[1]);
+
+            {
+                draw_header(
+                    &mut f,
+                    &layout_chunks[0].inner(&Margin {
+                        horizontal: 10,
+                        vertical: 0,
+                    }),
+                    &players[0],
+                    &header_status,
+                    banner.as_deref(),
+                )
+                .unwrap();
+            }
+
+            for (index, game_data) in players.iter_mut().enumerate() {
+                let player = if versus(&options) {
+                    Some(player_name(&options, index))
+                } else {
+                    None
+                };
+
+                if let Some(score) = &score {
+                    draw_message(&mut f, &chunks[index + 1], " Time over! ", score).unwrap();
+                    continue;
+                }
+
+                let hidden = index == 0
+                    && blindfold
+                        .as_ref()
+                        .is_some_and(|blindfold| blindfold.hidden(game_data));
+                let targets = tutorial
+                    .as_ref()
+                    .map(|tutorial| tutorial.targets(&game_data.arr_state))
+                    .unwrap_or_default();
+
+                draw_game(
+                    &mut f,
+                    &chunks[index + 1],
+                    game_data,
+                    player.as_deref(),
+                    length,
+                    &theme_system,
+                    hidden,
+                    &targets,
+                )
+                .unwrap();
+            }
+
+            if let Some(ghost) = &ghost {
+                draw_ghost(
+                    &mut f,
+                    &chunks[players.len() + 1],
+                    ghost,
+                    &players[0],
+                    length,
+                    &theme_system,
+                )
+                .unwrap();
+            }
+
+            if let Some(goal) = goal_preview {
+                draw_goal(
+                    &mut f,
+                    &chunks[players.len() + 1],
+                    goal,
+                    &players[0].goal,
+                    length,
+                    &theme_system,
+                )
+                .unwrap();
+            }
+
+            if analyzing {
+                draw_analysis(&mut f, &chunks[boards + 1], &players[0], analysis.as_ref()).unwrap();
+            } else if let Some(tutorial) = &tutorial {
+                let area = chunks[boards + 1];
+                let area = Rect::new(area.x, area.y, area.width, area.height.min(board_height));
+
+                draw_message(
+                    &mut f,
+                    &area,
+                    " Tutorial ",
+                    &tutorial_lines(tutorial, &players[0]),
+                )
+                .unwrap();
+            } else {
+                helper::draw_guide(
+                    &mut f,
+                    &chunks[boards + 1],
+                    options.race,
+                    &extra_commands(&options),
+                )
+                .unwrap();
+            }
+
+            {
+                let footer = "🍺 Github: 24seconds/rust-15-puzzle-cli";
+                let block = Block::default()
+                    .borders(Borders::NONE)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title(footer);
+                f.render_widget(block, footer_chunks[1]);
+            }
+        })?;
+
+        let key = match events.next()? {
+            Event::Input(key) => key,
+            Event::Tick => continue,
+            Event::Solver(event) => {
+                match event {
+                    SolverEvent::Progress(board, progress) => solving = Some((board, progress)),
+                    SolverEvent::Solved(board, length) if board == players[0].scramble => {
+                        solving = None;
+                        for game_data in players.iter_mut() {
+                            game_data.optimal_length = Some(length);
+                        }
+
+                        if options.challenge.is_some() {
+                            status = game_status(&players[0])?;
+                        }
+                    }
+                    SolverEvent::Cancelled(board) if board == players[0].scramble => {
+                        solving = None;
+
+                        if options.challenge.is_some() {
+                            // Without the optimal length there is no move limit to rank against.
+                            players[0].ranked = false;
+                            status = String::from(
+                                "Challenge: search cancelled, no move limit and not ranked",
+                            );
+                        } else if status.is_empty() {
+                            status = String::from("Search for the optimal length cancelled");
+                        }
+                    }
+                    _ => {}
+                }
+
+                continue;
+            }
+            Event::Remote(message) => {
+                if let Some(peer) = peer.as_mut() {
+                    match message {
+                        Message::New(board, difficulty) if !peer.host => {
+                            players =
+                                race_players(GameData::from_scramble(board, difficulty, None));
+                            request_optimal_length(&mut optimal_solver, &players[0]);
+                            winner = None;
+                        }
+                        Message::Move(operation, time) => {
+                            let opponent = &mut players[1];
+                            let was_done = opponent.game_state == GameState::DONE;
+                            replay_move(opponent, operation, time);
+
+                            if !was_done && opponent.game_state == GameState::DONE {
+                                winner = race_winner(winner, &players, 1);
+                            }
+                        }
+                        Message::Rematch(difficulty) if peer.host => {
+                            players = new_game(&mut rng, &options, difficulty)?;
+                            request_optimal_length(&mut optimal_solver, &players[0]);
+                            winner = None;
+                            send_message(peer, &new_message(&players[0]));
+                        }
+                        Message::Bye => {
+                            peer.status = String::from("Your opponent left the race");
+                        }
+                        _ => {}
+                    }
+                }
+
+                continue;
+            }
+        };
+
+        if let Some((index, operation)) = key_operation(key, options.race) {
+            let game_data = &mut players[index];
+            let was_done = game_data.game_state == GameState::DONE;
+            let move_count = game_data.move_count;
+            handle_move_operation(game_data, operation);
+
+            if let Some(tutorial) = &tutorial {
+                status = tutorial_status(tutorial, game_data);
+            }
+
+            if game_data.move_count != move_count {
+                let message = Message::Move(operation, game_data.elapsed_time());
+
+                if let Some(peer) = peer.as_mut() {
+                    send_message(peer, &message);
+                }
+                if let Some(broadcaster) = &broadcaster {
+                    broadcaster.send(&message);
+                }
+            }
+
+            // Running out of moves fails the challenge, only the first attempt is recorded.
+            if game_data.game_state == GameState::OUT_OF_MOVES
+                && game_data.move_count != move_count
+                && game_data.ranked
+            {
+                append_record(&solve_record(game_data, inspection.as_ref()))?;
+            }
+
+            if !was_done && game_data.game_state == GameState::DONE {
+                if game_data.ranked {
+                    append_record(&solve_record(game_data, inspection.as_ref()))?;
+                }
+                status = match &inspection {
+                    Some(inspection) => inspection_status(inspection, game_data)?,
+                    None => game_status(game_data)?,
+                };
+                if versus(&options) {
+                    winner = race_winner(winner, &players, index);
+                }
+
+                // Timing is shown right away, the comparison to the optimal solution follows on
+                // boards small enough to solve every position.
+                let board = &players[index].scramble;
+                if !versus(&options) && marathon.is_none() && options.drill.is_none() {
+                    if board.width() * board.height() <= ANALYSIS_MAX_CELLS {
+                        analyzer.request(&players[index]);
+                    }
+                    analyzing = true;
+                }
+            }
+        }
+
+        if key == Key::Esc
+            && solving
+                .as_ref()
+                .is_some_and(|(board, _)| *board == players[0].scramble)
+        {
+            optimal_solver.cancel();
+        }
+
+        let new_difficulty = match key {
+            Key::Char('r') => Some(players[0].difficulty),
+            Key::Char(c @ '1'..='4') => Some(Difficulty::ALL[c as usize - '1' as usize]),
+            _ => None,
+        };
+
+        if key == Key::Char('q') || new_difficulty.is_some() {
+            record_abandoned_game(&players[0], inspection.as_ref())?;
+        }
+
+        if let Some(difficulty) = new_difficulty {
+            match peer.as_mut() {
+                // Only the host deals scrambles, the new game starts once it sends one.
+                Some(peer) if !peer.host => send_message(peer, &Message::Rematch(difficulty)),
+                _ => {
+                    players = new_game(&mut rng, &options, difficulty)?;
+                    request_optimal_length(&mut optimal_solver, &players[0]);
+                    status = game_status(&players[0])?;
+                    ghost = new_ghost(&options, &players[0])?;
+                    winner = None;
+
+                    marathon = new_marathon(&options);
+                    if let Some(marathon) = marathon.as_mut() {
+                        marathon.start(&mut players[0]);
+                        status = marathon_status(marathon);
+                    }
+                    score = None;
+                    blindfold = new_blindfold(&options, &mut players[0]);
+                    inspection = options.inspection.map(Inspection::new);
+                    tutorial = new_tutorial(&options);
+                    if let Some(tutorial) = &tutorial {
+                        status = tutorial_status(tutorial, &players[0]);
+                    }
+                    hints = false;
+                    analyzer.cancel();
+                    analyzing = false;
+                    analysis = None;
+
+                    if let Some(peer) = peer.as_mut() {
+                        send_message(peer, &new_message(&players[0]));
+                    }
+                    if let Some(broadcaster) = &broadcaster {
+                        broadcaster.send(&new_message(&players[0]));
+                    }
+                }
+            }
+        }
+
+        match key {
+            Key::Char('q') => {
+                if let Some(peer) = peer.as_mut() {
+                    send_message(peer, &Message::Bye);
+                }
+                if let Some(broadcaster) = &broadcaster {
+                    broadcaster.send(&Message::Bye);
+                }
+                break;
+            }
+            // The opponent's clock can't be stopped from here.
+            Key::Char('p') if peer.is_none() => {
+                for game_data in players.iter_mut() {
+                    handle_pause(game_data);
+                }
+
+                if let Some(broadcaster) = &broadcaster {
+                    match players[0].game_state {
+                        GameState::PAUSED => {
+                            broadcaster.send(&Message::Pause(players[0].base_time))
+                        }
+                        GameState::PLAYING => broadcaster.send(&Message::Resume),
+                        _ => {}
+                    }
+                }
+            }
+            Key::Char('c') => {
+                theme_system = theme_system.change_theme();
+            }
+            Key::Char('\n') if blindfold.is_some() => {
+                let game_data = &mut players[0];
+                let was_playing = game_data.game_state == GameState::PLAYING;
+                finish_blindfold(game_data);
+
+                if was_playing && game_data.ranked {
+                    if let Some(blindfold) = &blindfold {
+                        append_record(&blindfold.to_record(game_data))?;
+                    }
+                }
+            }
+            Key::Char('\n') => {
+                if let Some(tutorial) = tutorial.as_mut() {
+                    let board = &players[0].arr_state;
+
+                    status = if tutorial.advance(board) {
+                        tutorial_status(tutorial, &players[0])
+                    } else {
+                        format!(
+                            "Not yet, {} isn't solved. Follow the steps on the right.",
+                            tutorial.stage().name
+                        )
+                    };
+                }
+            }
+            Key::Char('h') if hints_available(&options) => {
+                hints = !hints;
+                // Hinted games are practice.
+                players[0].ranked &= !hints;
+                status = if hints {
+                    hint_status(&mut table, &players[0])?
+                } else {
+                    game_status(&players[0])?
+                };
+            }
+            Key::Char('t') if options.challenge.is_some() => {
+                players = vec![players[0].retry()];
+                status = game_status(&players[0])?;
+                inspection = options.inspection.map(Inspection::new);
+                analyzer.cancel();
+                analyzing = false;
+                analysis = None;
+
+                if let Some(broadcaster) = &broadcaster {
+                    broadcaster.send(&new_message(&players[0]));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Maps a key to the player it belongs to and the move. Alone, w,a,s,d and arrows both move
+/// the only board, in a race the arrows belong to the second player.
+fn key_operation(key: Key, race: bool) -> Option<(usize, Operation)> {
+    let arrows_player = if race { 1 } else { 0 };
+
+    match key {
+        Key::Char('w') => Some((0, Operation::UP)),
+        Key::Char('a') => Some((0, Operation::LEFT)),
+        Key::Char('s') => Some((0, Operation::DOWN)),
+        Key::Char('d') => Some((0, Operation::RIGHT)),
+        Key::Up => Some((arrows_player, Operation::UP)),
+        Key::Left => Some((arrows_player, Operation::LEFT)),
+        Key::Down => Some((arrows_player, Operation::DOWN)),
+        Key::Right => Some((arrows_player, Operation::RIGHT)),
+        _ => None,
+    }
+}
+
+/// Commands of the game mode, listed below the common ones.
+fn extra_commands(options: &PlayOptions) -> Vec<&'static str> {
+    let mut commands = Vec::new();
+
+    if options.challenge.is_some() {
+        commands.push("Retry position : t");
+    }
+    if options.blindfold.is_some() {
+        commands.push("Finish blindfold solve : Enter");
+    }
+    if options.tutorial {
+        commands.push("Next tutorial stage : Enter");
+    }
+    if hints_available(options) {
+        commands.push("Toggle hints : h");
+    }
+
+    commands
+}
+
+/// Whether several boards are played against each other.
+fn versus(options: &PlayOptions) -> bool {
+    options.race || options.network.is_some()
+}
+
+fn player_name(options: &PlayOptions, index: usize) -> String {
+    match (&options.network, index) {
+        (Some(_), 0) => String::from("You"),
+        (Some(_), _) => String::from("Opponent"),
+        (None, _) => format!("Player {}", index + 1),
+    }
+}
+
+/// The first player to finish leads the race. Once everybody is done the fastest time wins,
+/// since each clock only starts with its player's first move.
+fn race_winner(winner: Option<usize>, players: &[GameData], finished: usize) -> Option<usize> {
+    if players
+        .iter()
+        .all(|game_data| game_data.game_state == GameState::DONE)
+    {
+        return (0..players.len()).min_by_key(|&index| players[index].base_time);
+    }
+
+    winner.or(Some(finished))
+}
+
+fn race_banner(options: &PlayOptions, players: &[GameData], winner: usize) -> String {
+    let game_data = &players[winner];
+
+    format!(
+        "{} won! {}s, {} moves. Press 'r' for a rematch!",
+        player_name(options, winner),
+        game_data.base_time.as_secs(),
+        game_data.move_count
+    )
+}
+
+/// One game per player, all starting from the same scramble.
+/// Fails on options that don't go together with the size of the board.
+fn check_options(options: &PlayOptions) -> Result<(), Box<dyn Error>> {
+    if options.challenge.is_some() && options.width * options.height > OPTIMAL_SCRAMBLE_MAX_CELLS {
+        return Err(format!(
+            "Challenges need the optimal solution length, boards can have at most {} cells",
+            OPTIMAL_SCRAMBLE_MAX_CELLS
+        )
+        .into());
+    }
+    if let Some(drill) = options.drill {
+        drill.check_size(options.width, options.height)?;
+    }
+    options.goal.board(options.width, options.height)?;
+
+    Ok(())
+}
+
+fn new_game(
+    rng: &mut ThreadRng,
+    options: &PlayOptions,
+    difficulty: Difficulty,
+) -> Result<Vec<GameData>, Box<dyn Error>> {
+    let game_data = if options.daily {
+        let date = daily::today();
+        let scramble = daily::daily_scramble(date, options.width, options.height);
+        let records = load_records()?;
+
+        let mut game_data = GameData::from_scramble(scramble, Difficulty::Expert, None);
+        game_data.mode = GameMode::Daily(date);
+        game_data.ranked = !daily::attempted(&records, date, options.width, options.height);
+
+        game_data
+    } else if let Some(drill) = options.drill {
+        let board = drill.generate(rng, options.width, options.height)?;
+
+        let mut game_data = GameData::from_scramble(board, difficulty, None);
+        game_data.mode = GameMode::Drill(drill);
+
+        game_data
+    } else {
+        let goal = options.goal.board(options.width, options.height)?;
+        let mut game_data = GameData::with_goal(rng, &goal, difficulty);
+        // Stats compare solves into the usual layout only, other goals are practice.
+        if options.goal != Goal::Standard {
+            game_data.ranked = false;
+        }
+        if let Some(extra) = options.challenge {
+            game_data.mode = GameMode::Challenge(extra);
+        }
+        // Tutorial games are practice, they don't go into stats.
+        if options.tutorial {
+            game_data.mode = GameMode::Tutorial;
+            game_data.ranked = false;
+        }
+
+        game_data
+    };
+
+    if !versus(options) {
+        return Ok(vec![game_data]);
+    }
+
+    Ok(race_players(game_data))
+}
+
+/// Races are played for fun, only solo games go into stats.
+fn race_players(game_data: GameData) -> Vec<GameData> {
+    (0..2)
+        .map(|_| {
+            let mut player = GameData::from_scramble(
+                game_data.scramble.clone(),
+                game_data.difficulty,
+                game_data.optimal_length,
+            );
+            player.mode = game_data.mode;
+            player.goal = game_data.goal.clone();
+            player.ranked = false;
+
+            player
+        })
+        .collect()
+}
+
+/// Sets up the connection of a networked race, the host waits here for its opponent.
+fn connect(network: &Network) -> Result<(Peer, MessageReader), Box<dyn Error>> {
+    let (stream, host) = match network {
+        Network::Host(address) => {
+            let listener = TcpListener::bind(net::with_default_port(address))?;
+            println!("Waiting for an opponent on {}...", listener.local_addr()?);
+
+            (listener.accept()?.0, true)
+        }
+        Network::Join(address) => (TcpStream::connect(net::with_default_port(address))?, false),
+    };
+
+    // Moves are tiny and should show up on the other side right away.
+    stream.set_nodelay(true)?;
+    let status = format!("Online race against {}", stream.peer_addr()?);
+    let reader = MessageReader::new(stream.try_clone()?);
+
+    Ok((
+        Peer {
+            stream,
+            host,
+            status,
+        },
+        reader,
+    ))
+}
+
+/// A failed send means the opponent is gone, which the race survives.
+fn send_message(peer: &mut Peer, message: &Message) {
+    if net::send(&mut peer.stream, message).is_err() {
+        peer.status = String::from("Connection to your opponent lost");
+    }
+}
+
+/// Announces the scramble of a new game.
+fn new_message(game_data: &GameData) -> Message {
+    Message::New(game_data.scramble.clone(), game_data.difficulty)
+}
+
+/// Expert scrambles aren't solved while generating them, so solve them in the background.
+/// Drills only solve part of the board, they are compared to their own reference instead.
+fn request_optimal_length(optimal_solver: &mut OptimalSolver, game_data: &GameData) {
+    let board = &game_data.scramble;
+
+    if game_data.optimal_length.is_none()
+        && !matches!(game_data.mode, GameMode::Drill(_))
+        && board.width() * board.height() <= OPTIMAL_SCRAMBLE_MAX_CELLS
+    {
+        optimal_solver.request(board, &game_data.goal);
+    }
+}
+
+fn solving_status(progress: &Progress) -> String {
+    format!(
+        "Searching the optimal length: at least {} moves, {} nodes  (Esc to cancel)",
+        progress.bound, progress.nodes_expanded
+    )
+}
+
+fn new_ghost(options: &PlayOptions, game_data: &GameData) -> Result<Option<Ghost>, Box<dyn Error>> {
+    if !options.ghost {
+        return Ok(None);
+    }
+
+    Ok(Ghost::best(&load_records()?, &game_data.scramble))
+}
+
+fn ghost_status(options: &PlayOptions, ghost: Option<&Ghost>) -> String {
+    match ghost {
+        Some(ghost) if ghost.same_scramble() => format!(
+            "Ghost: your best on this scramble, {:.1}s",
+            ghost.time().as_secs_f64()
+        ),
+        Some(ghost) => format!(
+            "Ghost: your best {}x{} solve, {:.1}s",
+            options.width,
+            options.height,
+            ghost.time().as_secs_f64()
+        ),
+        None if options.ghost => String::from("No recorded solve of this size yet to race against"),
+        None => String::new(),
+    }
+}
+
+/// Extra header line describing the game mode.
+fn game_status(game_data: &GameData) -> Result<String, Box<dyn Error>> {
+    let date = match (game_data.mode, game_data.optimal_length) {
+        (GameMode::Daily(date), _) => date,
+        (GameMode::Drill(drill), _) => {
+            return Ok(format!(
+                "Drill: {}, the row-by-row reference takes {} moves",
+                drill.description(),
+                drill.reference(&game_data.scramble)?
+            ))
+        }
+        (GameMode::Challenge(_), None) => {
+            return Ok(String::from(
+                "Challenge: solving the scramble to set the move limit...",
+            ))
+        }
+        (GameMode::Challenge(extra), Some(length)) => {
+            let attempt = if game_data.ranked { "" } else { "  (retry)" };
+
+            return Ok(format!(
+                "Challenge: solve in {} moves, optimal {} + {}{}",
+                length + extra,
+                length,
+                extra,
+                attempt
+            ));
+        }
+        _ => return Ok(String::new()),
+    };
+
+    let streak = daily::streak(&load_records()?, date);
+    let attempt = if game_data.ranked {
+        "ranked attempt"
+    } else {
+        "practice, today's ranked attempt is used"
+    };
+
+    Ok(format!(
+        "Daily {}  Streak: {} days  ({})",
+        date, streak, attempt
+    ))
+}
+
+fn new_marathon(options: &PlayOptions) -> Option<Marathon> {
+    options
+        .marathon
+        .map(Marathon::new)
+        .or_else(|| options.time_attack.map(Marathon::time_attack))
+}
+
+fn marathon_status(marathon: &Marathon) -> String {
+    let splits: Vec<String> = marathon
+        .splits()
+        .iter()
+        .map(|split| format!("{:.1}s", split.as_secs_f64()))
+        .collect();
+
+    let progress = match marathon.limit() {
+        Limit::Puzzles(puzzles) => format!(
+            "Marathon: puzzle {}/{}",
+            (marathon.solved() + 1).min(puzzles),
+            puzzles
+        ),
+        Limit::Time(_) => format!("Time attack: {} solved", marathon.solved()),
+    };
+
+    if splits.is_empty() {
+        progress
+    } else {
+        format!("{}  Splits: {}", progress, splits.join(" "))
+    }
+}
+
+fn marathon_banner(marathon: &Marathon) -> Option<String> {
+    if !marathon.finished() {
+        return None;
+    }
+
+    let banner = match marathon.limit() {
+        Limit::Puzzles(puzzles) => format!(
+            "Marathon done! {} puzzles in {:.1}s. Press 'r' to run it again!",
+            puzzles,
+            marathon.total_time().as_secs_f64()
+        ),
+        Limit::Time(_) => format!(
+            "Time over! {} puzzles solved. Press 'r' to try again!",
+            marathon.solved()
+        ),
+    };
+
+    Some(banner)
+}
+
+/// Lines of the final screen of a time attack, compared to the high score before this one.
+fn time_attack_score(
+    marathon: &Marathon,
+    game_data: &GameData,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let budget = match marathon.limit() {
+        Limit::Time(budget) => budget,
+        Limit::Puzzles(_) => return Ok(Vec::new()),
+    };
+    let size = (game_data.scramble.width(), game_data.scramble.height());
+    let solved = marathon.solved();
+
+    let mut lines = vec![format!(
+        "Solved {} puzzles of {}x{} in {}s",
+        solved,
+        size.0,
+        size.1,
+        budget.as_secs()
+    )];
+
+    lines.push(
+        match time_attack_high_score(&load_records()?, size, budget) {
+            None => String::from("First time attack of this size and length"),
+            Some(high_score) if solved > high_score => {
+                format!("New high score! Previous best: {}", high_score)
+            }
+            Some(high_score) => format!("High score: {}", high_score),
+        },
+    );
+
+    if let Some(best) = marathon.splits().iter().min() {
+        lines.push(format!(
+            "Best split: {:.1}s  Mean split: {:.1}s",
+            best.as_secs_f64(),
+            marathon.total_time().as_secs_f64() / solved as f64
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(String::from("Press 'r' to play again"));
+
+    Ok(lines)
+}
+
+fn challenge_banner(game_data: &GameData) -> Option<String> {
+    let limit = game_data.move_limit()?;
+
+    match game_data.game_state {
+        GameState::DONE => Some(format!(
+            "Challenge passed in {} of {} moves! Press 'r' for a new one!",
+            game_data.move_count, limit
+        )),
+        GameState::OUT_OF_MOVES => Some(String::from(
+            "Out of moves! Press 't' to retry the position or 'r' for a new one",
+        )),
+        _ => None,
+    }
+}
+
+fn drill_banner(game_data: &GameData) -> Option<String> {
+    match (game_data.mode, &game_data.game_state) {
+        (GameMode::Drill(_), GameState::DONE) => Some(format!(
+            "Drill done in {} moves, {:.1}s! Press 'r' for another one!",
+            game_data.move_count,
+            game_data.base_time.as_secs_f64()
+        )),
+        _ => None,
+    }
+}
+
+fn new_blindfold(options: &PlayOptions, game_data: &mut GameData) -> Option<Blindfold> {
+    options
+        .blindfold
+        .map(|memo_limit| Blindfold::new(memo_limit, game_data))
+}
+
+fn blindfold_status(blindfold: &Blindfold, game_data: &GameData) -> String {
+    match blindfold.memo_time() {
+        None => format!(
+            "Blindfold: memorize the board, the tiles hide on your first move or in {}s",
+            blindfold.memo_left().as_secs()
+        ),
+        Some(_) if blindfold.hidden(game_data) => {
+            String::from("Blindfold: solve it blind, press Enter when you think it is solved")
+        }
+        Some(memo_time) => format!(
+            "Blindfold: memo {:.1}s, solve {:.1}s",
+            memo_time.as_secs_f64(),
+            game_data.base_time.as_secs_f64()
+        ),
+    }
+}
+
+fn blindfold_banner(game_data: &GameData) -> Option<String> {
+    match game_data.game_state {
+        GameState::DONE => Some(String::from("Solved blind! Press 'r' for another one!")),
+        GameState::UNSOLVED => Some(String::from(
+            "Not solved, here is the board. Press 'r' to try another one!",
+        )),
+        _ => None,
+    }
+}
+
+fn inspection_status(
+    inspection: &Inspection,
+    game_data: &GameData,
+) -> Result<String, Box<dyn Error>> {
+    let used = match inspection.used() {
+        Some(used) => used,
+        None if inspection.penalty().is_some() => {
+            return Ok(String::from("Inspection over, start now for a +2 penalty!"))
+        }
+        None => {
+            let left = inspection.limit().saturating_sub(inspection.elapsed());
+            let warning = inspection
+                .warning()
+                .map(|seconds| format!("  {} seconds!", seconds))
+                .unwrap_or_default();
+
+            return Ok(format!(
+                "Inspection: {}s left, the clock starts with your first move{}",
+                left.as_secs() + 1,
+                warning
+            ));
+        }
+    };
+
+    let penalty = match inspection.penalty() {
+        Some(Penalty::PlusTwo) => " (+2)",
+        Some(Penalty::Dnf) => " (DNF)",
+        None => "",
+    };
+    let inspected = format!("Inspected {:.1}s{}", used.as_secs_f64(), penalty);
+
+    Ok(match game_status(game_data)? {
+        status if status.is_empty() => inspected,
+        status => format!("{}  {}", status, inspected),
+    })
+}
+
+fn inspection_banner(inspection: &Inspection, game_data: &GameData) -> Option<String> {
+    if game_data.game_state == GameState::TIME_OVER && inspection.penalty() == Some(Penalty::Dnf) {
+        return Some(String::from(
+            "DNF: started more than 2s after the inspection. Press 'r' for a new game!",
+        ));
+    }
+
+    None
+}
+
+/// Hints come from a table of every position, only kept for small boards.
+fn hints_available(options: &PlayOptions) -> bool {
+    !versus(options)
+        && options.width * options.height <= TABLE_MAX_CELLS
+        && options.goal == Goal::Standard
+}
+
+fn hint_status(
+    table: &mut Option<DistanceTable>,
+    game_data: &GameData,
+) -> Result<String, Box<dyn Error>> {
+    let board = &game_data.arr_state;
+    let table = match table {
+        Some(table) => table,
+        None => table.insert(DistanceTable::load_or_build(board.width(), board.height())?),
+    };
+
+    let hint = match game_data.game_state {
+        GameState::INIT | GameState::PLAYING => table.hint(board),
+        _ => None,
+    };
+
+    Ok(match (hint, table.distance(board)) {
+        (Some(operation), Some(distance)) => format!(
+            "Hint: press '{}', solved in {} moves at best (hinted games aren't recorded)",
+            operation.to_char(),
+            distance
+        ),
+        _ => String::from("Hints on, press 'h' to turn them off"),
+    })
+}
+
+fn new_tutorial(options: &PlayOptions) -> Option<Tutorial> {
+    if !options.tutorial {
+        return None;
+    }
+
+    Some(Tutorial::new(options.width, options.height))
+}
+
+fn tutorial_status(tutorial: &Tutorial, game_data: &GameData) -> String {
+    let stage = tutorial.stage();
+    let state = if tutorial.stage_done(&game_data.arr_state) {
+        "done"
+    } else {
+        "highlighted tiles go next"
+    };
+
+    format!(
+        "Tutorial: stage {}/{}, {} ({})",
+        tutorial.current() + 1,
+        tutorial.stages().len(),
+        stage.name,
+        state
+    )
+}
+
+/// The steps of the current stage of the tutorial, and the stages around it.
+fn tutorial_lines(tutorial: &Tutorial, game_data: &GameData) -> Vec<String> {
+    let stages: Vec<String> = tutorial
+        .stages()
+        .iter()
+        .enumerate()
+        .map(|(index, stage)| {
+            let mark = match index.cmp(&tutorial.current()) {
+                std::cmp::Ordering::Less => "✓",
+                std::cmp::Ordering::Equal => ">",
+                std::cmp::Ordering::Greater => " ",
+            };
+
+            format!("{} {}", mark, stage.name)
+        })
+        .collect();
+
+    let mut lines = vec![stages.join("  "), String::new()];
+    lines.extend(tutorial.prompt(&game_data.arr_state));
+
+    lines
+}
+
+/// A ranked daily attempt counts once started, so leaving it unsolved is recorded as DNF.
+fn record_abandoned_game(
+    game_data: &GameData,
+    inspection: Option<&Inspection>,
+) -> Result<(), Box<dyn Error>> {
+    let started =
+        game_data.game_state == GameState::PLAYING || game_data.game_state == GameState::PAUSED;
+
+    if started && game_data.ranked && matches!(game_data.mode, GameMode::Daily(_)) {
+        append_record(&solve_record(game_data, inspection))?;
+    }
+
+    Ok(())
+}
+
+/// The result of a game, with the inspection before it when it was timed.
+fn solve_record(game_data: &GameData, inspection: Option<&Inspection>) -> SolveRecord {
+    let record = SolveRecord::from_game(game_data);
+
+    match inspection {
+        Some(inspection) => inspection.apply(record),
+        None => record,
+    }
+}
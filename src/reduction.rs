@@ -0,0 +1,410 @@
+//! A fast, non-optimal solver following the row-by-row method a human would use: the top row
+//! and the left column are solved tile by tile until a 2x2 block is left. It works on boards of
+//! any size, far beyond what the optimal solver can search.
+
+use crate::board::{is_solvable, Board, Operation};
+use crate::method::{stages, Stage, StageKind};
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    error::Error,
+};
+
+/// The moves of one stage of the method.
+pub struct Phase {
+    pub name: String,
+    pub moves: Vec<Operation>,
+}
+
+pub struct ReductionSolution {
+    pub phases: Vec<Phase>,
+}
+
+impl ReductionSolution {
+    /// All the moves, phase after phase.
+    pub fn moves(&self) -> Vec<Operation> {
+        self.phases
+            .iter()
+            .flat_map(|phase| phase.moves.iter().copied())
+            .collect()
+    }
+}
+
+/// Solves `board` stage by stage, as listed by `method::stages`.
+pub fn solve_reduction(board: &Board) -> Result<ReductionSolution, Box<dyn Error>> {
+    if !is_solvable(board) {
+        return Err("Board is not solvable!".into());
+    }
+
+    let mut solver = Reduction::new(board);
+    let mut phases = Vec::new();
+
+    for stage in stages(board.width(), board.height()) {
+        match stage.kind {
+            StageKind::Final => solver.solve_block(&stage)?,
+            StageKind::Row | StageKind::Column => solver.solve_line(&stage)?,
+        }
+
+        phases.push(Phase {
+            name: stage.name,
+            moves: solver.moves.split_off(0),
+        });
+    }
+
+    Ok(ReductionSolution { phases })
+}
+
+struct Reduction {
+    width: usize,
+    tiles: Vec<u16>,
+    blank: usize,
+    /// Cells the blank must not disturb, the solved ones.
+    locked: Vec<bool>,
+    moves: Vec<Operation>,
+}
+
+impl Reduction {
+    fn new(board: &Board) -> Reduction {
+        Reduction {
+            width: board.width(),
+            tiles: board.tiles().to_vec(),
+            blank: board.blank_index(),
+            locked: vec![false; board.tiles().len()],
+            moves: Vec::new(),
+        }
+    }
+
+    /// Places the tiles of a row or a column, one by one and the last two together.
+    fn solve_line(&mut self, stage: &Stage) -> Result<(), Box<dyn Error>> {
+        let cells = &stage.cells;
+        let (before_corner, corner) = (cells[cells.len() - 2], cells[cells.len() - 1]);
+
+        for &cell in cells[..cells.len() - 2].iter() {
+            self.place(cell as u16 + 1, cell)?;
+            self.locked[cell] = true;
+        }
+
+        let (tile_before, tile_corner) = (before_corner as u16 + 1, corner as u16 + 1);
+        if self.tiles[before_corner] != tile_before || self.tiles[corner] != tile_corner {
+            // The last two tiles can't go in one after the other. The first one waits in the
+            // corner while the other one is brought next to it, then both are put in place
+            // within a small window around the corner.
+            let (beside, window) = match stage.kind {
+                StageKind::Row => {
+                    let below = corner + self.width;
+                    let window = vec![
+                        before_corner,
+                        corner,
+                        before_corner + self.width,
+                        below,
+                        before_corner + 2 * self.width,
+                        below + self.width,
+                    ];
+
+                    (below, window)
+                }
+                StageKind::Column | StageKind::Final => {
+                    let window = vec![
+                        before_corner,
+                        before_corner + 1,
+                        before_corner + 2,
+                        corner,
+                        corner + 1,
+                        corner + 2,
+                    ];
+
+                    (corner + 1, window)
+                }
+            };
+
+            // Already within the window, the search finds the shortest way in.
+            let ready = window.contains(&self.blank)
+                && window.contains(&self.position(tile_before)?)
+                && window.contains(&self.position(tile_corner)?);
+
+            if !ready {
+                self.place(tile_before, corner)?;
+                self.locked[corner] = true;
+                if !window.contains(&self.position(tile_corner)?) {
+                    self.place(tile_corner, beside)?;
+                }
+                self.locked[corner] = false;
+            }
+
+            self.solve_window(&window, (tile_before, before_corner), (tile_corner, corner))?;
+        }
+
+        for &cell in cells.iter() {
+            self.locked[cell] = true;
+        }
+
+        Ok(())
+    }
+
+    /// Puts the two tiles `first` and `second` on their cells with a breadth-first search over
+    /// the positions of both tiles and the blank in `window`, the other tiles being all alike.
+    fn solve_window(
+        &mut self,
+        window: &[usize],
+        first: (u16, usize),
+        second: (u16, usize),
+    ) -> Result<(), Box<dyn Error>> {
+        let (first_cell, second_cell) = (self.position(first.0)?, self.position(second.0)?);
+
+        // Bring the blank into the window without disturbing the two tiles.
+        if !window.contains(&self.blank) {
+            self.locked[first_cell] = true;
+            self.locked[second_cell] = true;
+            let entry = window
+                .iter()
+                .filter(|cell| **cell != first_cell && **cell != second_cell)
+                .filter_map(|cell| self.path(self.blank, *cell, None))
+                .min_by_key(|path| path.len())
+                .ok_or("The blank is cut off from the last two tiles")?;
+            self.locked[first_cell] = false;
+            self.locked[second_cell] = false;
+
+            for cell in entry {
+                self.swap(cell);
+            }
+        }
+
+        let start = (first_cell, second_cell, self.blank);
+        let goal = (first.1, second.1);
+        let mut previous = HashMap::new();
+        let mut queue = VecDeque::new();
+        previous.insert(start, start);
+        queue.push_back(start);
+
+        while let Some(state) = queue.pop_front() {
+            if (state.0, state.1) == goal {
+                let mut blanks = Vec::new();
+                let mut state = state;
+
+                while state != start {
+                    blanks.push(state.2);
+                    state = previous[&state];
+                }
+
+                for cell in blanks.into_iter().rev() {
+                    self.swap(cell);
+                }
+
+                return Ok(());
+            }
+
+            let (first, second, blank) = state;
+            for &next in window.iter().filter(|cell| self.adjacent(blank, **cell)) {
+                let moved = |cell: usize| if cell == next { blank } else { cell };
+                let next_state = (moved(first), moved(second), next);
+
+                if let Entry::Vacant(entry) = previous.entry(next_state) {
+                    entry.insert(state);
+                    queue.push_back(next_state);
+                }
+            }
+        }
+
+        Err("Could not place the last two tiles".into())
+    }
+
+    /// Cycles the blank around the final 2x2 block, the shorter way round.
+    fn solve_block(&mut self, stage: &Stage) -> Result<(), Box<dyn Error>> {
+        // The blank's goal is the bottom right cell, the one left out of the stage.
+        let top_left = stage.cells[0];
+        let clockwise = [
+            top_left,
+            top_left + 1,
+            top_left + self.width + 1,
+            top_left + self.width,
+        ];
+        let solved = |tiles: &[u16]| {
+            stage
+                .cells
+                .iter()
+                .all(|cell| tiles[*cell] as usize == cell + 1)
+        };
+
+        let mut best: Option<Vec<usize>> = None;
+        for cycle in [
+            clockwise,
+            [clockwise[0], clockwise[3], clockwise[2], clockwise[1]],
+        ]
+        .iter()
+        {
+            let (mut tiles, mut blank) = (self.tiles.clone(), self.blank);
+            let mut path = Vec::new();
+            let mut position = cycle.iter().position(|cell| *cell == blank).ok_or(
+                "The blank is outside of the final block, the board was not reduced correctly",
+            )?;
+
+            // Three tiles rotate through twelve positions before coming back.
+            while !solved(&tiles) && path.len() < 12 {
+                position = (position + 1) % 4;
+                tiles.swap(blank, cycle[position]);
+                blank = cycle[position];
+                path.push(blank);
+            }
+
+            if solved(&tiles) && best.as_ref().is_none_or(|best| path.len() < best.len()) {
+                best = Some(path);
+            }
+        }
+
+        for cell in best.ok_or("Could not solve the final block")? {
+            self.swap(cell);
+        }
+
+        Ok(())
+    }
+
+    fn position(&self, tile: u16) -> Result<usize, Box<dyn Error>> {
+        Ok(self
+            .tiles
+            .iter()
+            .position(|t| *t == tile)
+            .ok_or_else(|| format!("Missing tile {}", tile))?)
+    }
+
+    fn adjacent(&self, a: usize, b: usize) -> bool {
+        let same_row = a / self.width == b / self.width;
+
+        (same_row && a.abs_diff(b) == 1) || a.abs_diff(b) == self.width
+    }
+
+    /// Moves `tile` to `target` one cell at a time, walking the blank around it without
+    /// touching the locked cells.
+    fn place(&mut self, tile: u16, target: usize) -> Result<(), Box<dyn Error>> {
+        let mut position = self.position(tile)?;
+
+        let path = self
+            .path(position, target, None)
+            .ok_or_else(|| format!("No way to bring tile {} to its place", tile))?;
+
+        for next in path {
+            self.move_blank(next, Some(position))?;
+            self.swap(position);
+            position = next;
+        }
+
+        Ok(())
+    }
+
+    /// Brings the blank to `target`, around the `avoid` cell and the locked ones.
+    fn move_blank(&mut self, target: usize, avoid: Option<usize>) -> Result<(), Box<dyn Error>> {
+        let path = self
+            .path(self.blank, target, avoid)
+            .ok_or("The blank is cut off from the unsolved part of the board")?;
+
+        for cell in path {
+            self.swap(cell);
+        }
+
+        Ok(())
+    }
+
+    /// Swaps the blank with the tile on the neighboring `cell`.
+    fn swap(&mut self, cell: usize) {
+        let operation = if cell == self.blank + 1 {
+            Operation::LEFT
+        } else if cell + 1 == self.blank {
+            Operation::RIGHT
+        } else if cell == self.blank + self.width {
+            Operation::UP
+        } else {
+            Operation::DOWN
+        };
+
+        self.tiles.swap(self.blank, cell);
+        self.blank = cell;
+        self.moves.push(operation);
+    }
+
+    /// Shortest path of cells from `from` to `to`, excluding `from`, through unlocked cells
+    /// other than `avoid`.
+    fn path(&self, from: usize, to: usize, avoid: Option<usize>) -> Option<Vec<usize>> {
+        let height = self.tiles.len() / self.width;
+        let mut previous = vec![usize::MAX; self.tiles.len()];
+        let mut queue = VecDeque::new();
+        previous[from] = from;
+        queue.push_back(from);
+
+        while let Some(cell) = queue.pop_front() {
+            if cell == to {
+                let mut path = Vec::new();
+                let mut cell = to;
+
+                while cell != from {
+                    path.push(cell);
+                    cell = previous[cell];
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            let (column, row) = (cell % self.width, cell / self.width);
+            let neighbors = [
+                (column > 0).then(|| cell - 1),
+                (column + 1 < self.width).then(|| cell + 1),
+                (row > 0).then(|| cell - self.width),
+                (row + 1 < height).then(|| cell + self.width),
+            ];
+
+            for next in neighbors.iter().flatten() {
+                if previous[*next] == usize::MAX && !self.locked[*next] && Some(*next) != avoid {
+                    previous[*next] = cell;
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{is_done, move_tile};
+    use crate::scramble::shuffle_arr;
+
+    fn apply(board: &Board, moves: &[Operation]) -> Board {
+        moves.iter().fold(board.clone(), |board, operation| {
+            move_tile(&board, *operation)
+        })
+    }
+
+    #[test]
+    fn reduction_should_solve_boards_of_any_size() -> Result<(), Box<dyn Error>> {
+        let mut rng = rand::thread_rng();
+
+        for &(width, height) in [(2, 2), (3, 3), (4, 4), (5, 3), (2, 6), (10, 10), (13, 7)].iter() {
+            for _ in 0..20 {
+                let board = shuffle_arr(&mut rng, width, height);
+                let solution = solve_reduction(&board)?;
+
+                assert!(is_done(&apply(&board, &solution.moves())), "{}", board);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reduction_should_group_moves_by_phase() -> Result<(), Box<dyn Error>> {
+        let board: Board = "1,2,3/4,5,6/0,7,8".parse()?;
+        let solution = solve_reduction(&board)?;
+        let names: Vec<&str> = solution
+            .phases
+            .iter()
+            .map(|phase| phase.name.as_str())
+            .collect();
+
+        assert_eq!(names, vec!["row 1", "column 1", "final 2x2"]);
+        assert!(solution.phases[0].moves.is_empty());
+        assert!(is_done(&apply(&board, &solution.moves())));
+        assert!(solve_reduction(&"2,1,3/4,5,6/7,8,0".parse()?).is_err());
+
+        Ok(())
+    }
+}
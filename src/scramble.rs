@@ -0,0 +1,307 @@
+//! Random scrambles.
+
+use crate::board::{is_solvable_to, move_tile, target_index, Board, Operation};
+use crate::heuristic::Heuristic;
+use crate::solver::solve_to;
+use rand::{seq::SliceRandom, Rng};
+use std::{error::Error, fmt, ops::RangeInclusive, str::FromStr};
+
+/// Boards up to this many cells are graded by their optimal solution length, bigger ones only
+/// by random-walk depth since solving them optimally takes too long.
+pub const OPTIMAL_SCRAMBLE_MAX_CELLS: usize = 16;
+
+const MAX_SCRAMBLE_ATTEMPTS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    /// Range of optimal solution lengths for a board of `width` x `height`, scaled by the
+    /// number of tiles. `Expert` has no range, it is a uniformly random position.
+    pub fn length_range(self, width: usize, height: usize) -> Option<RangeInclusive<usize>> {
+        let tiles = width * height - 1;
+
+        match self {
+            Difficulty::Easy => Some((tiles / 2).max(1)..=tiles),
+            Difficulty::Medium => Some(tiles + 1..=2 * tiles),
+            Difficulty::Hard => Some(2 * tiles + 1..=3 * tiles),
+            Difficulty::Expert => None,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Difficulty, Box<dyn Error>> {
+        Difficulty::ALL
+            .iter()
+            .find(|difficulty| difficulty.name() == s.to_lowercase())
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "Invalid difficulty '{}', expected easy, medium, hard or expert",
+                    s
+                )
+                .into()
+            })
+    }
+}
+
+pub struct Scramble {
+    pub board: Board,
+    /// Known when the board was graded by solving it.
+    pub optimal_length: Option<usize>,
+}
+
+/// Returns a uniformly random solvable board that is not already solved.
+pub fn shuffle_arr<R: Rng + ?Sized>(rng: &mut R, width: usize, height: usize) -> Board {
+    shuffle_to(rng, &Board::solved(width, height))
+}
+
+/// Returns a uniformly random board that can be solved into `goal`, other than `goal` itself.
+/// Half of the shuffles can't; swapping the first two tiles of those flips their parity and
+/// pairs each of them with exactly one solvable position, so every position stays equally
+/// likely.
+pub fn shuffle_to<R: Rng + ?Sized>(rng: &mut R, goal: &Board) -> Board {
+    let (width, height) = (goal.width(), goal.height());
+    let mut arr: Vec<u16> = (0..width * height).map(|tile| tile as u16).collect();
+
+    loop {
+        arr.shuffle(rng);
+
+        let mut board = Board::new(width, height, arr.clone()).unwrap();
+        if !is_solvable_to(&board, goal) {
+            let tiles: Vec<usize> = (0..arr.len()).filter(|cell| arr[*cell] != 0).collect();
+            arr.swap(tiles[0], tiles[1]);
+            board = Board::new(width, height, arr.clone()).unwrap();
+        }
+
+        // Only the goal itself is drawn again, one chance in half the positions.
+        if board != *goal {
+            break board;
+        }
+    }
+}
+
+/// Moves the blank `depth` times from the solved board, never undoing the previous move.
+pub fn random_walk<R: Rng + ?Sized>(
+    rng: &mut R,
+    width: usize,
+    height: usize,
+    depth: usize,
+) -> Board {
+    random_walk_from(rng, &Board::solved(width, height), depth)
+}
+
+/// Moves the blank `depth` times from `start`, never undoing the previous move.
+pub fn random_walk_from<R: Rng + ?Sized>(rng: &mut R, start: &Board, depth: usize) -> Board {
+    let mut board = start.clone();
+    let mut previous: Option<Operation> = None;
+
+    for _ in 0..depth {
+        let operations: Vec<Operation> = Operation::ALL
+            .iter()
+            .copied()
+            .filter(|operation| previous != Some(operation.inverse()))
+            .filter(|operation| target_index(&board, *operation).is_some())
+            .collect();
+        let operation = *operations.choose(rng).unwrap();
+
+        board = move_tile(&board, operation);
+        previous = Some(operation);
+    }
+
+    board
+}
+
+/// Returns a scramble of the given difficulty. Small boards are solved to make sure the optimal
+/// length falls in `Difficulty::length_range`, bigger ones use the range as random-walk depth.
+pub fn scramble_board<R: Rng + ?Sized>(
+    rng: &mut R,
+    width: usize,
+    height: usize,
+    difficulty: Difficulty,
+) -> Scramble {
+    scramble_to(rng, &Board::solved(width, height), difficulty)
+}
+
+/// Like `scramble_board`, for a board solved into `goal`. Any goal works: only boards of up to
+/// `OPTIMAL_SCRAMBLE_MAX_CELLS` are searched, and walks from the goal can always go back.
+pub fn scramble_to<R: Rng + ?Sized>(rng: &mut R, goal: &Board, difficulty: Difficulty) -> Scramble {
+    let (width, height) = (goal.width(), goal.height());
+    let range = match difficulty.length_range(width, height) {
+        Some(range) => range,
+        None => {
+            return Scramble {
+                board: shuffle_to(rng, goal),
+                optimal_length: None,
+            }
+        }
+    };
+
+    if width * height > OPTIMAL_SCRAMBLE_MAX_CELLS {
+        let depth = rng.gen_range(*range.start(), *range.end() + 1);
+
+        return Scramble {
+            board: random_walk_from(rng, goal, depth),
+            optimal_length: None,
+        };
+    }
+
+    // A walk of `depth` moves is never longer than `depth` to solve, so start at the top of the
+    // range and walk further while the results come out too easy. Tiny boards can't reach every
+    // range, so give up after a while and keep the closest scramble.
+    let mut depth = *range.end();
+    let mut closest: Option<(usize, Board, usize)> = None;
+
+    for _ in 0..MAX_SCRAMBLE_ATTEMPTS {
+        let board = random_walk_from(rng, goal, depth);
+        // Walks from the goal always lead back to it, and boards this small are searched fast.
+        let length = solve_to(&board, goal, Heuristic::LinearConflict)
+            .expect("a random walk from the goal is solvable into it")
+            .moves
+            .len();
+
+        let miss = if length < *range.start() {
+            range.start() - length
+        } else {
+            length.saturating_sub(*range.end())
+        };
+        if closest.as_ref().is_none_or(|(best, _, _)| miss < *best) {
+            closest = Some((miss, board, length));
+        }
+        if miss == 0 {
+            break;
+        }
+
+        if length < *range.start() {
+            depth += 1 + (range.end() - range.start()) / 4;
+        } else {
+            depth = (depth - 1).max(*range.end());
+        }
+    }
+
+    let (_, board, length) = closest.expect("at least one scramble is tried");
+
+    Scramble {
+        board,
+        optimal_length: Some(length),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{is_done, is_solvable};
+    use crate::goal::Goal;
+    use crate::solver::solve;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::HashMap;
+
+    #[test]
+    fn scramble_should_match_difficulty() {
+        let mut rng = rand::thread_rng();
+
+        for difficulty in Difficulty::ALL.iter() {
+            let scramble = scramble_board(&mut rng, 3, 3, *difficulty);
+            assert!(is_solvable(&scramble.board));
+
+            if let Some(range) = difficulty.length_range(3, 3) {
+                let length = scramble.optimal_length.unwrap();
+                assert!(range.contains(&length));
+                assert_eq!(solve(&scramble.board).unwrap().moves.len(), length);
+            }
+        }
+
+        // No 2x2 position is far enough for hard, the closest one is returned instead.
+        let scramble = scramble_board(&mut rng, 2, 2, Difficulty::Hard);
+        assert!(scramble.optimal_length.is_some());
+
+        let scramble = scramble_board(&mut rng, 8, 8, Difficulty::Hard);
+        assert!(is_solvable(&scramble.board));
+        assert_eq!(scramble.optimal_length, None);
+
+        // The biggest board numbers its tiles up to u16::MAX.
+        let scramble = scramble_board(&mut rng, 256, 256, Difficulty::Expert);
+        assert!(is_solvable(&scramble.board));
+
+        // Other goals get scrambles of their own, half of them can't reach the usual layout.
+        let goal = Goal::Spiral.board(3, 3).unwrap();
+        for difficulty in [Difficulty::Medium, Difficulty::Expert].iter() {
+            let scramble = scramble_to(&mut rng, &goal, *difficulty);
+            assert!(is_solvable_to(&scramble.board, &goal));
+            assert_ne!(scramble.board, goal);
+        }
+    }
+
+    #[test]
+    fn shuffle_should_be_uniform_over_positions() {
+        // Fixed seed, the test always sees the same draws.
+        let mut rng = ChaCha8Rng::seed_from_u64(15);
+
+        for &(width, height, positions) in [(2, 2, 11), (3, 2, 359)].iter() {
+            let draws_per_position = 200;
+            let mut counts: HashMap<Board, usize> = HashMap::new();
+            for _ in 0..positions * draws_per_position {
+                let board = shuffle_arr(&mut rng, width, height);
+                assert!(is_solvable(&board) && !is_done(&board));
+                *counts.entry(board).or_insert(0) += 1;
+            }
+            assert_eq!(counts.len(), positions);
+
+            // Pearson's chi-squared statistic, with positions - 1 degrees of freedom. Its mean is
+            // the degrees of freedom and a uniform draw stays within five standard deviations.
+            let expected = draws_per_position as f64;
+            let chi_squared: f64 = counts
+                .values()
+                .map(|count| (*count as f64 - expected).powi(2) / expected)
+                .sum();
+            let freedom = (positions - 1) as f64;
+            assert!(
+                chi_squared < freedom + 5.0 * (2.0 * freedom).sqrt(),
+                "{}x{}: chi-squared {}",
+                width,
+                height,
+                chi_squared
+            );
+        }
+    }
+
+    #[test]
+    fn difficulty_should_parse() -> Result<(), Box<dyn Error>> {
+        assert_eq!("easy".parse::<Difficulty>()?, Difficulty::Easy);
+        assert_eq!("Expert".parse::<Difficulty>()?, Difficulty::Expert);
+        assert!("impossible".parse::<Difficulty>().is_err());
+
+        Ok(())
+    }
+}
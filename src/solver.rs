@@ -0,0 +1,500 @@
+//! Optimal IDA* solver.
+
+use crate::board::{is_solvable_to, swap_index, Board, Operation};
+use crate::heuristic::{Heuristic, PatternDatabase, WalkingDistance};
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Nodes expanded between two checks of the cancel flag, each also reporting progress.
+const CHECK_INTERVAL: u64 = 1 << 16;
+
+pub struct Solution {
+    pub moves: Vec<Operation>,
+    pub nodes_expanded: u64,
+}
+
+/// How far a running search got: no solution is shorter than `bound`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub bound: u32,
+    pub nodes_expanded: u64,
+}
+
+/// Finds an optimal solution with IDA*, using Manhattan distance plus linear conflicts.
+pub fn solve(board: &Board) -> Result<Solution, Box<dyn Error>> {
+    solve_with(board, Heuristic::LinearConflict)
+}
+
+/// Finds an optimal solution with IDA*, guided by `heuristic`. The solution length is the same
+/// whatever the heuristic, only the number of nodes searched changes.
+pub fn solve_with(board: &Board, heuristic: Heuristic) -> Result<Solution, Box<dyn Error>> {
+    solve_to(
+        board,
+        &Board::solved(board.width(), board.height()),
+        heuristic,
+    )
+}
+
+/// Finds an optimal solution into the `goal` layout.
+pub fn solve_to(
+    board: &Board,
+    goal: &Board,
+    heuristic: Heuristic,
+) -> Result<Solution, Box<dyn Error>> {
+    let solution = solve_cancellable(board, goal, heuristic, &AtomicBool::new(false), &mut |_| {})?;
+
+    Ok(solution.ok_or("Search cancelled")?)
+}
+
+/// Like `solve_to`, for searches that may take long: `report` hears about each new bound and
+/// regularly in between, and setting `cancel` stops the search, which then returns `None`.
+pub fn solve_cancellable(
+    board: &Board,
+    goal: &Board,
+    heuristic: Heuristic,
+    cancel: &AtomicBool,
+    report: &mut dyn FnMut(Progress),
+) -> Result<Option<Solution>, Box<dyn Error>> {
+    if !is_solvable_to(board, goal) {
+        return Err("Board is not solvable!".into());
+    }
+
+    let mut search = Search::new(board, goal, heuristic, cancel, report)?;
+    let mut bound = search.heuristic();
+
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        search.bound = bound;
+        search.report();
+
+        match search.dfs(0, bound, None) {
+            SearchResult::Found => {
+                return Ok(Some(Solution {
+                    moves: search.path,
+                    nodes_expanded: search.nodes_expanded,
+                }))
+            }
+            SearchResult::NextBound(next_bound) => bound = next_bound,
+            SearchResult::Cancelled => return Ok(None),
+        }
+    }
+}
+
+enum SearchResult {
+    Found,
+    NextBound(u32),
+    Cancelled,
+}
+
+struct Search<'a> {
+    width: usize,
+    height: usize,
+    tiles: Vec<u16>,
+    blank: usize,
+    /// Goal cell of each tile.
+    goals: Vec<usize>,
+    heuristic: Heuristic,
+    manhattan: u32,
+    row_conflicts: Vec<u32>,
+    column_conflicts: Vec<u32>,
+    /// Walking distance tables and the current row and column states.
+    walking: Option<(Arc<WalkingDistance>, u64, u64)>,
+    /// Pattern databases and the current index of each group.
+    patterns: Option<(Arc<PatternDatabase>, Vec<usize>)>,
+    path: Vec<Operation>,
+    nodes_expanded: u64,
+    /// The bound of the current iteration, for progress reports.
+    bound: u32,
+    cancel: &'a AtomicBool,
+    report: &'a mut dyn FnMut(Progress),
+}
+
+impl<'a> Search<'a> {
+    fn new(
+        board: &Board,
+        goal: &Board,
+        heuristic: Heuristic,
+        cancel: &'a AtomicBool,
+        report: &'a mut dyn FnMut(Progress),
+    ) -> Result<Search<'a>, Box<dyn Error>> {
+        let (width, height) = (board.width(), board.height());
+        let mut goals = vec![0; goal.tiles().len()];
+        for (cell, tile) in goal.tiles().iter().enumerate() {
+            goals[*tile as usize] = cell;
+        }
+
+        let mut search = Search {
+            width,
+            height,
+            tiles: board.tiles().to_vec(),
+            blank: board.blank_index(),
+            goals,
+            heuristic,
+            manhattan: 0,
+            row_conflicts: vec![0; height],
+            column_conflicts: vec![0; width],
+            walking: None,
+            patterns: None,
+            path: Vec::new(),
+            nodes_expanded: 0,
+            bound: 0,
+            cancel,
+            report,
+        };
+
+        search.manhattan = (0..search.tiles.len())
+            .map(|index| search.distance(search.tiles[index], index))
+            .sum();
+
+        match heuristic {
+            Heuristic::Manhattan => {}
+            Heuristic::LinearConflict => {
+                for row in 0..height {
+                    search.row_conflicts[row] = search.row_conflict(row);
+                }
+                for column in 0..width {
+                    search.column_conflicts[column] = search.column_conflict(column);
+                }
+            }
+            Heuristic::WalkingDistance => {
+                let tables = WalkingDistance::get(goal)?;
+                let placed = search
+                    .tiles
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tile)| **tile != 0);
+                let rows = tables.rows.encode(
+                    placed
+                        .clone()
+                        .map(|(index, tile)| (index / width, search.goal(*tile).0)),
+                    search.blank / width,
+                );
+                let columns = tables.columns.encode(
+                    placed.map(|(index, tile)| (index % width, search.goal(*tile).1)),
+                    search.blank % width,
+                );
+
+                search.walking = Some((tables, rows, columns));
+            }
+            Heuristic::PatternDatabase => {
+                let database = PatternDatabase::get(goal)?;
+                let indexes = database.indexes(&search.tiles);
+
+                search.patterns = Some((database, indexes));
+            }
+        }
+
+        Ok(search)
+    }
+
+    fn heuristic(&self) -> u32 {
+        match self.heuristic {
+            Heuristic::Manhattan => self.manhattan,
+            Heuristic::LinearConflict => {
+                let conflicts: u32 = self.row_conflicts.iter().sum::<u32>()
+                    + self.column_conflicts.iter().sum::<u32>();
+
+                self.manhattan + 2 * conflicts
+            }
+            Heuristic::WalkingDistance => match &self.walking {
+                Some((tables, rows, columns)) => {
+                    tables.rows.distance(*rows) + tables.columns.distance(*columns)
+                }
+                None => self.manhattan,
+            },
+            Heuristic::PatternDatabase => match &self.patterns {
+                Some((database, indexes)) => database.distance(indexes),
+                None => self.manhattan,
+            },
+        }
+    }
+
+    fn goal(&self, tile: u16) -> (usize, usize) {
+        let cell = self.goals[tile as usize];
+
+        (cell / self.width, cell % self.width)
+    }
+
+    fn distance(&self, tile: u16, index: usize) -> u32 {
+        if tile == 0 {
+            return 0;
+        }
+
+        let width = self.width;
+        let (goal_row, goal_column) = self.goal(tile);
+        let (row, column) = (index / width, index % width);
+
+        (goal_row as i32 - row as i32).unsigned_abs()
+            + (goal_column as i32 - column as i32).unsigned_abs()
+    }
+
+    fn row_conflict(&self, row: usize) -> u32 {
+        let width = self.width;
+        let goals: Vec<usize> = (0..width)
+            .map(|column| self.tiles[row * width + column])
+            .filter(|tile| *tile != 0 && self.goal(*tile).0 == row)
+            .map(|tile| self.goal(tile).1)
+            .collect();
+
+        line_conflict(&goals)
+    }
+
+    fn column_conflict(&self, column: usize) -> u32 {
+        let width = self.width;
+        let goals: Vec<usize> = (0..self.height)
+            .map(|row| self.tiles[row * width + column])
+            .filter(|tile| *tile != 0 && self.goal(*tile).1 == column)
+            .map(|tile| self.goal(tile).0)
+            .collect();
+
+        line_conflict(&goals)
+    }
+
+    fn apply(&mut self, operation: Operation, index_to_swap: usize) {
+        let width = self.width;
+        let tile = self.tiles[index_to_swap];
+
+        self.manhattan =
+            self.manhattan + self.distance(tile, self.blank) - self.distance(tile, index_to_swap);
+        self.tiles.swap(self.blank, index_to_swap);
+
+        let (from, to) = (index_to_swap, self.blank);
+        self.blank = index_to_swap;
+
+        match self.heuristic {
+            Heuristic::Manhattan => {}
+            Heuristic::LinearConflict => match operation {
+                Operation::UP | Operation::DOWN => {
+                    self.row_conflicts[from / width] = self.row_conflict(from / width);
+                    self.row_conflicts[to / width] = self.row_conflict(to / width);
+                }
+                Operation::LEFT | Operation::RIGHT => {
+                    self.column_conflicts[from % width] = self.column_conflict(from % width);
+                    self.column_conflicts[to % width] = self.column_conflict(to % width);
+                }
+            },
+            Heuristic::WalkingDistance => {
+                let (goal_row, goal_column) = self.goal(tile);
+
+                if let Some((tables, rows, columns)) = &mut self.walking {
+                    match operation {
+                        Operation::UP | Operation::DOWN => {
+                            *rows = tables.rows.moved(*rows, from / width, to / width, goal_row);
+                        }
+                        Operation::LEFT | Operation::RIGHT => {
+                            *columns = tables.columns.moved(
+                                *columns,
+                                from % width,
+                                to % width,
+                                goal_column,
+                            );
+                        }
+                    }
+                }
+            }
+            Heuristic::PatternDatabase => {
+                if let Some((database, indexes)) = &mut self.patterns {
+                    database.moved(indexes, tile, from, to);
+                }
+            }
+        }
+    }
+
+    fn report(&mut self) {
+        (self.report)(Progress {
+            bound: self.bound,
+            nodes_expanded: self.nodes_expanded,
+        });
+    }
+
+    fn dfs(&mut self, cost: u32, bound: u32, previous: Option<Operation>) -> SearchResult {
+        let heuristic = self.heuristic();
+        let estimate = cost + heuristic;
+
+        if estimate > bound {
+            return SearchResult::NextBound(estimate);
+        }
+        if heuristic == 0 {
+            return SearchResult::Found;
+        }
+
+        self.nodes_expanded += 1;
+        if self.nodes_expanded.is_multiple_of(CHECK_INTERVAL) {
+            if self.cancel.load(Ordering::Relaxed) {
+                return SearchResult::Cancelled;
+            }
+            self.report();
+        }
+
+        let (width, height) = (self.width, self.height);
+        let mut next_bound = u32::MAX;
+
+        for operation in Operation::ALL.iter() {
+            if previous == Some(operation.inverse()) {
+                continue;
+            }
+
+            let index_to_swap = match swap_index(width, height, self.blank, *operation) {
+                Some(index) => index,
+                None => continue,
+            };
+            let blank = self.blank;
+
+            self.apply(*operation, index_to_swap);
+            self.path.push(*operation);
+
+            match self.dfs(cost + 1, bound, Some(*operation)) {
+                SearchResult::Found => return SearchResult::Found,
+                SearchResult::NextBound(bound) => next_bound = next_bound.min(bound),
+                SearchResult::Cancelled => return SearchResult::Cancelled,
+            }
+
+            self.path.pop();
+            self.apply(operation.inverse(), blank);
+        }
+
+        SearchResult::NextBound(next_bound)
+    }
+}
+
+/// Number of tiles that must leave a line so the remaining ones are in goal order.
+fn line_conflict(goals: &[usize]) -> u32 {
+    let mut longest = vec![1; goals.len()];
+
+    for i in 0..goals.len() {
+        for j in 0..i {
+            if goals[j] < goals[i] {
+                longest[i] = longest[i].max(longest[j] + 1);
+            }
+        }
+    }
+
+    (goals.len() - longest.iter().max().copied().unwrap_or(0)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{is_done, move_tile, parse_moves};
+
+    fn apply_moves(board: &Board, moves: &[Operation]) -> Board {
+        moves.iter().fold(board.clone(), |board, operation| {
+            move_tile(&board, *operation)
+        })
+    }
+
+    #[test]
+    fn line_conflict_should_correct() {
+        assert_eq!(line_conflict(&[]), 0);
+        assert_eq!(line_conflict(&[0, 1, 2, 3]), 0);
+        assert_eq!(line_conflict(&[1, 0]), 1);
+        assert_eq!(line_conflict(&[3, 2, 1, 0]), 3);
+        assert_eq!(line_conflict(&[2, 0, 1]), 1);
+    }
+
+    #[test]
+    fn solve_should_find_optimal_solution() -> Result<(), Box<dyn Error>> {
+        {
+            let solution = solve(&Board::solved(4, 4))?;
+            assert_eq!(solution.moves.len(), 0);
+        }
+
+        {
+            // One of the two hardest 3x3 positions.
+            let board: Board = "8,6,7/2,5,4/3,0,1".parse()?;
+            let solution = solve(&board)?;
+            assert_eq!(solution.moves.len(), 31);
+            assert!(is_done(&apply_moves(&board, &solution.moves)));
+        }
+
+        {
+            let scramble = parse_moves("sasawdwassdwaawddsaw")?;
+            let board = apply_moves(&Board::solved(4, 4), &scramble);
+            let solution = solve(&board)?;
+            assert!(solution.moves.len() <= scramble.len());
+            assert!(is_done(&apply_moves(&board, &solution.moves)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn solve_should_find_same_length_with_every_heuristic() -> Result<(), Box<dyn Error>> {
+        let boards: Vec<Board> = vec![
+            "8,6,7/2,5,4/3,0,1".parse()?,
+            "0,3,2/1,5,4".parse()?,
+            apply_moves(&Board::solved(4, 4), &parse_moves("sasawdwassdwaawddsaw")?),
+        ];
+
+        for board in boards.iter() {
+            let length = solve(board)?.moves.len();
+
+            for heuristic in Heuristic::ALL.iter() {
+                let solution = solve_with(board, *heuristic)?;
+                assert_eq!(solution.moves.len(), length, "{}", heuristic);
+                assert!(is_done(&apply_moves(board, &solution.moves)));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn solve_should_reach_other_goals() -> Result<(), Box<dyn Error>> {
+        let goal: Board = "1,2,3/8,0,4/7,6,5".parse()?;
+        let board = apply_moves(&goal, &parse_moves("wasdwwaassdd")?);
+
+        for heuristic in Heuristic::ALL.iter() {
+            let solution = solve_to(&board, &goal, *heuristic)?;
+            assert!(solution.moves.len() <= 12, "{}", heuristic);
+            assert_eq!(apply_moves(&board, &solution.moves), goal);
+        }
+
+        assert!(solve_to(&Board::solved(3, 3), &goal, Heuristic::Manhattan).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn solve_should_report_progress_until_cancelled() -> Result<(), Box<dyn Error>> {
+        let board: Board = "8,6,7/2,5,4/3,0,1".parse()?;
+        let mut bounds = Vec::new();
+        let solution = solve_cancellable(
+            &board,
+            &Board::solved(3, 3),
+            Heuristic::LinearConflict,
+            &AtomicBool::new(false),
+            &mut |progress| bounds.push(progress.bound),
+        )?;
+
+        assert_eq!(solution.map(|solution| solution.moves.len()), Some(31));
+        assert!(bounds.windows(2).all(|bounds| bounds[0] <= bounds[1]));
+        assert_eq!(bounds.last(), Some(&31));
+
+        let cancelled = solve_cancellable(
+            &board,
+            &Board::solved(3, 3),
+            Heuristic::Manhattan,
+            &AtomicBool::new(true),
+            &mut |_| {},
+        )?;
+        assert!(cancelled.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn solve_should_reject_unsolvable_board() -> Result<(), Box<dyn Error>> {
+        let board: Board = "2,1,3/4,5,6/7,8,0".parse()?;
+        assert!(solve(&board).is_err());
+
+        Ok(())
+    }
+}
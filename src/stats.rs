@@ -0,0 +1,461 @@
+//! Results of finished games, persisted in the data directory.
+
+use crate::board::{format_moves, parse_moves, Board, Operation};
+use crate::game::{GameData, GameMode, GameState};
+use crate::inspection::Penalty;
+use crate::scramble::Difficulty;
+use std::{
+    env,
+    error::Error,
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// One finished game, stored as a line of `key=value` pairs in the results file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveRecord {
+    pub finished_at: u64,
+    pub scramble: Board,
+    pub moves: Vec<Operation>,
+    /// Elapsed time of each move, empty in records written before it was kept.
+    pub move_times: Vec<Duration>,
+    pub time: Duration,
+    pub difficulty: Option<Difficulty>,
+    pub optimal_length: Option<usize>,
+    pub mode: GameMode,
+    /// Time of each puzzle of a marathon. Marathon records start from the first scramble and
+    /// hold the moves of all puzzles one after another.
+    pub splits: Vec<Duration>,
+    /// Time spent memorizing the scramble of a blindfold solve, included in `time`.
+    pub memo_time: Option<Duration>,
+    /// Time spent inspecting before the solve, when it was timed.
+    pub inspection_time: Option<Duration>,
+    /// Penalty for a late start after inspecting, a +2 is included in `time`.
+    pub penalty: Option<Penalty>,
+    /// Number of times the game was paused, the board stays hidden while paused.
+    pub pauses: usize,
+    /// The game was abandoned before it was solved.
+    pub dnf: bool,
+}
+
+impl SolveRecord {
+    pub fn new(scramble: Board, moves: Vec<Operation>, time: Duration) -> SolveRecord {
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        SolveRecord {
+            finished_at,
+            scramble,
+            moves,
+            move_times: Vec::new(),
+            time,
+            difficulty: None,
+            optimal_length: None,
+            mode: GameMode::Classic,
+            splits: Vec::new(),
+            memo_time: None,
+            inspection_time: None,
+            penalty: None,
+            pauses: 0,
+            dnf: false,
+        }
+    }
+
+    pub fn from_game(game_data: &GameData) -> SolveRecord {
+        SolveRecord {
+            difficulty: Some(game_data.difficulty),
+            optimal_length: game_data.optimal_length,
+            move_times: game_data.move_times.clone(),
+            mode: game_data.mode,
+            pauses: game_data.pauses,
+            // Drills finish with only their part of the board solved, the game knows best.
+            dnf: game_data.game_state != GameState::DONE,
+            ..SolveRecord::new(
+                game_data.scramble.clone(),
+                game_data.moves.clone(),
+                game_data.elapsed_time(),
+            )
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.scramble.width(), self.scramble.height())
+    }
+
+    /// Whether the record is a single puzzle rather than a series like a marathon.
+    pub fn is_single_solve(&self) -> bool {
+        !matches!(self.mode, GameMode::Marathon(_) | GameMode::TimeAttack(_))
+    }
+}
+
+impl fmt::Display for SolveRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "finished_at={} scramble={} moves={} time_ms={}",
+            self.finished_at,
+            self.scramble,
+            format_moves(&self.moves),
+            self.time.as_millis()
+        )?;
+
+        if let Some(difficulty) = self.difficulty {
+            write!(f, " difficulty={}", difficulty)?;
+        }
+        if let Some(optimal_length) = self.optimal_length {
+            write!(f, " optimal={}", optimal_length)?;
+        }
+        match self.mode {
+            GameMode::Classic => {}
+            GameMode::Daily(date) => write!(f, " mode=daily date={}", date)?,
+            GameMode::Marathon(puzzles) => write!(f, " mode=marathon puzzles={}", puzzles)?,
+            GameMode::TimeAttack(budget) => {
+                write!(f, " mode=time_attack budget_ms={}", budget.as_millis())?
+            }
+            GameMode::Challenge(extra) => write!(f, " mode=challenge extra={}", extra)?,
+            GameMode::Blindfold => write!(f, " mode=blindfold")?,
+            GameMode::Tutorial => write!(f, " mode=tutorial")?,
+            GameMode::Drill(drill) => write!(f, " mode=drill drill={}", drill)?,
+        }
+        if let Some(memo_time) = self.memo_time {
+            write!(f, " memo_ms={}", memo_time.as_millis())?;
+        }
+        if let Some(inspection_time) = self.inspection_time {
+            write!(f, " inspection_ms={}", inspection_time.as_millis())?;
+        }
+        if let Some(penalty) = self.penalty {
+            write!(f, " penalty={}", penalty)?;
+        }
+        if self.pauses > 0 {
+            write!(f, " pauses={}", self.pauses)?;
+        }
+        if self.dnf {
+            write!(f, " dnf=true")?;
+        }
+        if !self.move_times.is_empty() {
+            write!(f, " move_times_ms={}", format_millis(&self.move_times))?;
+        }
+        if !self.splits.is_empty() {
+            write!(f, " splits_ms={}", format_millis(&self.splits))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SolveRecord {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<SolveRecord, Box<dyn Error>> {
+        let mut finished_at = None;
+        let mut scramble = None;
+        let mut moves = None;
+        let mut time = None;
+        let mut difficulty = None;
+        let mut optimal_length = None;
+        let mut mode = "classic";
+        let mut date = None;
+        let mut dnf = false;
+        let mut move_times = Vec::new();
+        let mut puzzles = None;
+        let mut budget = None;
+        let mut extra = None;
+        let mut drill = None;
+        let mut memo_time = None;
+        let mut inspection_time = None;
+        let mut penalty = None;
+        let mut pauses = 0;
+        let mut splits = Vec::new();
+
+        // Unknown keys are skipped so older builds can still read newer results.
+        for pair in s.split_whitespace() {
+            let (key, value) = match pair.find('=') {
+                Some(index) => (&pair[..index], &pair[index + 1..]),
+                None => return Err(format!("Invalid field '{}'", pair).into()),
+            };
+
+            match key {
+                "finished_at" => finished_at = Some(value.parse()?),
+                "scramble" => scramble = Some(value.parse()?),
+                "moves" => moves = Some(parse_moves(value)?),
+                "time_ms" => time = Some(Duration::from_millis(value.parse()?)),
+                "difficulty" => difficulty = Some(value.parse()?),
+                "optimal" => optimal_length = Some(value.parse()?),
+                "mode" => mode = value,
+                "date" => date = Some(value.parse()?),
+                "dnf" => dnf = value.parse()?,
+                "move_times_ms" => move_times = parse_millis(value)?,
+                "puzzles" => puzzles = Some(value.parse()?),
+                "budget_ms" => budget = Some(Duration::from_millis(value.parse()?)),
+                "splits_ms" => splits = parse_millis(value)?,
+                "extra" => extra = Some(value.parse()?),
+                "drill" => drill = Some(value.parse()?),
+                "memo_ms" => memo_time = Some(Duration::from_millis(value.parse()?)),
+                "inspection_ms" => inspection_time = Some(Duration::from_millis(value.parse()?)),
+                "penalty" => penalty = Some(value.parse()?),
+                "pauses" => pauses = value.parse()?,
+                _ => {}
+            }
+        }
+
+        let mode = match mode {
+            "daily" => GameMode::Daily(date.ok_or("Missing date of daily puzzle")?),
+            "marathon" => GameMode::Marathon(puzzles.ok_or("Missing puzzles of marathon")?),
+            "time_attack" => GameMode::TimeAttack(budget.ok_or("Missing budget of time attack")?),
+            "challenge" => GameMode::Challenge(extra.ok_or("Missing extra moves of challenge")?),
+            "blindfold" => GameMode::Blindfold,
+            "tutorial" => GameMode::Tutorial,
+            "drill" => GameMode::Drill(drill.ok_or("Missing drill")?),
+            _ => GameMode::Classic,
+        };
+
+        Ok(SolveRecord {
+            finished_at: finished_at.ok_or("Missing finished_at")?,
+            scramble: scramble.ok_or("Missing scramble")?,
+            moves: moves.ok_or("Missing moves")?,
+            move_times,
+            time: time.ok_or("Missing time_ms")?,
+            difficulty,
+            optimal_length,
+            mode,
+            splits,
+            memo_time,
+            inspection_time,
+            penalty,
+            pauses,
+            dnf,
+        })
+    }
+}
+
+fn format_millis(times: &[Duration]) -> String {
+    let times: Vec<String> = times
+        .iter()
+        .map(|time| time.as_millis().to_string())
+        .collect();
+
+    times.join(",")
+}
+
+fn parse_millis(s: &str) -> Result<Vec<Duration>, Box<dyn Error>> {
+    s.split(',')
+        .map(|time| Ok(Duration::from_millis(time.parse()?)))
+        .collect()
+}
+
+/// Directory for persisted data, `RUST_15_PUZZLE_DATA_DIR` overrides the XDG default.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("RUST_15_PUZZLE_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    let base = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".local").join("share"))
+            .unwrap_or_else(|| PathBuf::from(".")),
+    };
+
+    base.join("rust-15-puzzle-cli")
+}
+
+pub fn results_path() -> PathBuf {
+    data_dir().join("results.txt")
+}
+
+pub fn append_record(record: &SolveRecord) -> Result<(), Box<dyn Error>> {
+    let path = results_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", record)?;
+
+    Ok(())
+}
+
+pub fn load_records() -> Result<Vec<SolveRecord>, Box<dyn Error>> {
+    let path = results_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    Ok(parse_records(&path.display().to_string(), &content))
+}
+
+/// Parses the lines of a results file. A malformed line, e.g. one cut short by a crash,
+/// is skipped with a warning so it doesn't hide every other result.
+fn parse_records(name: &str, content: &str) -> Vec<SolveRecord> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match line.parse() {
+            Ok(record) => Some(record),
+            Err(e) => {
+                eprintln!("Skipping {}:{}: {}", name, index + 1, e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drill::Drill;
+
+    #[test]
+    fn solve_record_should_round_trip() -> Result<(), Box<dyn Error>> {
+        let record = SolveRecord {
+            finished_at: 1_600_000_000,
+            scramble: "1,2,3/4,5,6/7,0,8".parse()?,
+            moves: parse_moves("a")?,
+            move_times: Vec::new(),
+            time: Duration::from_millis(1234),
+            difficulty: None,
+            optimal_length: None,
+            mode: GameMode::Classic,
+            splits: Vec::new(),
+            memo_time: None,
+            inspection_time: None,
+            penalty: None,
+            pauses: 0,
+            dnf: false,
+        };
+
+        let line = record.to_string();
+        assert_eq!(
+            line,
+            "finished_at=1600000000 scramble=1,2,3/4,5,6/7,0,8 moves=a time_ms=1234"
+        );
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+        assert_eq!(
+            format!("{} unknown=1", line).parse::<SolveRecord>()?,
+            record
+        );
+
+        let record = SolveRecord {
+            difficulty: Some(Difficulty::Easy),
+            optimal_length: Some(1),
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.ends_with(" difficulty=easy optimal=1"));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        let record = SolveRecord {
+            mode: GameMode::Daily("2020-05-01".parse()?),
+            dnf: true,
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.ends_with(" mode=daily date=2020-05-01 dnf=true"));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        let record = SolveRecord {
+            moves: parse_moves("aw")?,
+            move_times: vec![Duration::from_millis(0), Duration::from_millis(450)],
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.ends_with(" move_times_ms=0,450"));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        let record = SolveRecord {
+            mode: GameMode::Marathon(2),
+            splits: vec![Duration::from_millis(300), Duration::from_millis(150)],
+            dnf: false,
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.contains(" mode=marathon puzzles=2 "));
+        assert!(line.ends_with(" splits_ms=300,150"));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+        assert!(!record.is_single_solve());
+
+        let record = SolveRecord {
+            mode: GameMode::TimeAttack(Duration::from_secs(120)),
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.contains(" mode=time_attack budget_ms=120000 "));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        let record = SolveRecord {
+            mode: GameMode::Challenge(2),
+            splits: Vec::new(),
+            dnf: true,
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.contains(" mode=challenge extra=2 dnf=true"));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+        assert!(record.is_single_solve());
+
+        let record = SolveRecord {
+            mode: GameMode::Blindfold,
+            memo_time: Some(Duration::from_millis(20_000)),
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.contains(" mode=blindfold memo_ms=20000 dnf=true"));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        let record = SolveRecord {
+            mode: GameMode::Classic,
+            memo_time: None,
+            inspection_time: Some(Duration::from_millis(16_200)),
+            penalty: Some(Penalty::PlusTwo),
+            dnf: false,
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.contains(" inspection_ms=16200 penalty=+2 "));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        let record = SolveRecord {
+            pauses: 3,
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.contains(" penalty=+2 pauses=3 "));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        let record = SolveRecord {
+            mode: GameMode::Drill(Drill::RowPair),
+            ..record
+        };
+        let line = record.to_string();
+        assert!(line.contains(" mode=drill drill=row-pair "));
+        assert_eq!(line.parse::<SolveRecord>()?, record);
+
+        assert!("finished_at=1 moves=a".parse::<SolveRecord>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_records_should_skip_malformed_lines() {
+        let content = concat!(
+            "finished_at=1 scramble=1,2,3/4,5,6/7,0,8 moves=a time_ms=1000\n",
+            "finished_at=2 scramble=1,2,3/4,5,6/7,0\n",
+            "\n",
+            "finished_at=3 scramble=1,2,3/4,5,6/7,0,8 moves=a time_ms=900\n",
+        );
+
+        let records = parse_records("results.txt", content);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].finished_at, 1);
+        assert_eq!(records[1].finished_at, 3);
+        assert_eq!(records[1].time, Duration::from_millis(900));
+    }
+}
@@ -0,0 +1,231 @@
+//! Optimal distances of every position of small boards, found with a breadth-first search back
+//! from the solved board. A 3x3 board has 181,440 solvable positions, small enough to keep all
+//! of them and answer hints and difficulty questions instantly.
+
+use crate::board::{is_solvable, move_tile, Board, Operation};
+use crate::stats::data_dir;
+use std::{collections::VecDeque, error::Error, fs, path::PathBuf};
+
+/// Boards up to this many cells get a table, 3x3 and 2x4 included.
+pub const TABLE_MAX_CELLS: usize = 9;
+
+/// Marks positions not reached yet while searching.
+const UNKNOWN: u8 = u8::MAX;
+
+/// Distances stored one byte per solvable position, in `rank` order.
+pub struct DistanceTable {
+    width: usize,
+    height: usize,
+    distances: Vec<u8>,
+}
+
+impl DistanceTable {
+    /// Searches the whole state space of the board size.
+    pub fn build(width: usize, height: usize) -> Result<DistanceTable, Box<dyn Error>> {
+        check_size(width, height)?;
+
+        let solved = Board::solved(width, height);
+        let mut distances = vec![UNKNOWN; positions(width * height)];
+        let mut queue = VecDeque::new();
+        distances[rank(solved.tiles())] = 0;
+        queue.push_back(solved);
+
+        while let Some(board) = queue.pop_front() {
+            let distance = distances[rank(board.tiles())];
+
+            for operation in Operation::ALL.iter() {
+                let next = move_tile(&board, *operation);
+                let index = rank(next.tiles());
+
+                if distances[index] == UNKNOWN {
+                    distances[index] = distance + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Ok(DistanceTable {
+            width,
+            height,
+            distances,
+        })
+    }
+
+    /// Reads the table of the size from the data directory, searching and saving it there the
+    /// first time.
+    pub fn load_or_build(width: usize, height: usize) -> Result<DistanceTable, Box<dyn Error>> {
+        let path = table_path(width, height);
+
+        if let Ok(bytes) = fs::read(&path) {
+            if let Ok(table) = DistanceTable::from_bytes(width, height, bytes) {
+                return Ok(table);
+            }
+        }
+
+        let table = DistanceTable::build(width, height)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, &table.distances)?;
+
+        Ok(table)
+    }
+
+    pub fn from_bytes(
+        width: usize,
+        height: usize,
+        bytes: Vec<u8>,
+    ) -> Result<DistanceTable, Box<dyn Error>> {
+        check_size(width, height)?;
+
+        if bytes.len() != positions(width * height) || bytes.contains(&UNKNOWN) {
+            return Err(format!("Invalid distance table for {}x{}", width, height).into());
+        }
+
+        Ok(DistanceTable {
+            width,
+            height,
+            distances: bytes,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.distances
+    }
+
+    /// Optimal solution length of `board`, `None` when it is unsolvable or of another size.
+    pub fn distance(&self, board: &Board) -> Option<usize> {
+        if (board.width(), board.height()) != (self.width, self.height) || !is_solvable(board) {
+            return None;
+        }
+
+        Some(self.distances[rank(board.tiles())] as usize)
+    }
+
+    /// A move on an optimal path to solved, `None` once solved.
+    pub fn hint(&self, board: &Board) -> Option<Operation> {
+        let closer = self.distance(board)?.checked_sub(1)?;
+
+        Operation::ALL.iter().copied().find(|operation| {
+            let next = move_tile(board, *operation);
+
+            next != *board && self.distance(&next) == Some(closer)
+        })
+    }
+
+    /// Number of positions at each distance, the longest one being God's number of the size.
+    pub fn distribution(&self) -> Vec<usize> {
+        let max = self.distances.iter().copied().max().unwrap_or(0) as usize;
+        let mut counts = vec![0; max + 1];
+
+        for distance in self.distances.iter() {
+            counts[*distance as usize] += 1;
+        }
+
+        counts
+    }
+}
+
+pub fn table_path(width: usize, height: usize) -> PathBuf {
+    data_dir()
+        .join("tables")
+        .join(format!("{}x{}.bin", width, height))
+}
+
+/// Fails on boards too big to keep every position of.
+pub fn check_size(width: usize, height: usize) -> Result<(), Box<dyn Error>> {
+    if width < 2 || height < 2 || width * height > TABLE_MAX_CELLS {
+        return Err(format!(
+            "Distance tables are only kept for boards of up to {} cells",
+            TABLE_MAX_CELLS
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Number of solvable positions of a board of `cells` cells, half of the permutations.
+fn positions(cells: usize) -> usize {
+    (3..=cells).product()
+}
+
+/// Index of a solvable position among all of them, ranking where the blank and each tile are.
+/// Swapping the last two tiles makes a position unsolvable, so where they are follows from the
+/// others and only the first `cells - 2` are ranked.
+fn rank(tiles: &[u16]) -> usize {
+    let cells = tiles.len();
+    let mut places = vec![0; cells];
+    for (place, tile) in tiles.iter().enumerate() {
+        places[*tile as usize] = place;
+    }
+
+    let mut used = vec![false; cells];
+    let mut index = 0;
+
+    for (tile, place) in places[..cells - 2].iter().enumerate() {
+        let smaller_unused = used[..*place].iter().filter(|used| !**used).count();
+
+        index = index * (cells - tile) + smaller_unused;
+        used[*place] = true;
+    }
+
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble::shuffle_arr;
+    use crate::solver::solve;
+
+    #[test]
+    fn table_should_verify_gods_numbers() -> Result<(), Box<dyn Error>> {
+        for &(width, height, positions, gods_number) in [
+            (2, 2, 12, 6),
+            (3, 2, 360, 21),
+            (2, 4, 20_160, 36),
+            (3, 3, 181_440, 31),
+        ]
+        .iter()
+        {
+            let distribution = DistanceTable::build(width, height)?.distribution();
+
+            assert_eq!(distribution.iter().sum::<usize>(), positions);
+            assert_eq!(distribution.len() - 1, gods_number);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_should_match_optimal_solver() -> Result<(), Box<dyn Error>> {
+        let table = DistanceTable::build(3, 3)?;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..20 {
+            let board = shuffle_arr(&mut rng, 3, 3);
+            let distance = table.distance(&board);
+            assert_eq!(distance, Some(solve(&board)?.moves.len()));
+            if distance == Some(0) {
+                continue;
+            }
+
+            let hint = table.hint(&board).ok_or("Missing hint")?;
+            assert_eq!(
+                table.distance(&move_tile(&board, hint)),
+                distance.map(|distance| distance - 1)
+            );
+        }
+
+        assert_eq!(table.hint(&Board::solved(3, 3)), None);
+        assert_eq!(table.distance(&"2,1,3/4,5,6/7,8,0".parse()?), None);
+
+        let bytes = table.as_bytes().to_vec();
+        let loaded = DistanceTable::from_bytes(3, 3, bytes)?;
+        assert_eq!(loaded.distance(&Board::solved(3, 3)), Some(0));
+        assert!(DistanceTable::from_bytes(3, 3, vec![0; 10]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Timing of the moves of a solve: the time between moves, long pauses to recognize the next
+//! moves and the time spent on each stage of the row-by-row method.
+
+use crate::board::{move_tile, Board, Operation};
+use crate::method::{progress, stages};
+use std::time::Duration;
+
+/// A pause is long when it is this many times the median time between moves...
+const LONG_PAUSE_FACTOR: u32 = 3;
+/// ...and at least this long.
+const LONG_PAUSE_MIN: Duration = Duration::from_secs(1);
+
+/// Time before each move, the first one counted from the start of the clock.
+pub fn move_intervals(move_times: &[Duration]) -> Vec<Duration> {
+    let mut previous = Duration::from_secs(0);
+
+    move_times
+        .iter()
+        .map(|time| {
+            let interval = time.saturating_sub(previous);
+            previous = *time;
+
+            interval
+        })
+        .collect()
+}
+
+/// Indices of the moves that came after a long pause, compared to the pace of the solve.
+pub fn long_pauses(intervals: &[Duration]) -> Vec<usize> {
+    let mut sorted = intervals.to_vec();
+    sorted.sort();
+
+    let threshold = match sorted.get(sorted.len() / 2) {
+        Some(median) => (*median * LONG_PAUSE_FACTOR).max(LONG_PAUSE_MIN),
+        None => return Vec::new(),
+    };
+
+    intervals
+        .iter()
+        .enumerate()
+        .filter(|(_, interval)| **interval >= threshold)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Time spent on each stage of `method::stages`. Each move counts towards the first stage that
+/// wasn't solved before it.
+pub fn stage_times(
+    scramble: &Board,
+    moves: &[Operation],
+    move_times: &[Duration],
+) -> Vec<Duration> {
+    let stages = stages(scramble.width(), scramble.height());
+    let mut times = vec![Duration::from_secs(0); stages.len()];
+    let mut board = scramble.clone();
+
+    for (operation, interval) in moves.iter().zip(move_intervals(move_times)) {
+        let stage = progress(&stages, &board).min(stages.len() - 1);
+        times[stage] += interval;
+        board = move_tile(&board, *operation);
+    }
+
+    times
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::parse_moves;
+    use std::error::Error;
+
+    fn millis(times: &[u64]) -> Vec<Duration> {
+        times.iter().map(|ms| Duration::from_millis(*ms)).collect()
+    }
+
+    #[test]
+    fn long_pauses_should_stand_out_from_pace() {
+        let intervals = move_intervals(&millis(&[300, 600, 900, 3000, 3300, 3600]));
+        assert_eq!(intervals, millis(&[300, 300, 300, 2100, 300, 300]));
+        assert_eq!(long_pauses(&intervals), vec![3]);
+
+        // Slow but steady solves have no long pauses.
+        assert!(long_pauses(&millis(&[900, 800, 950])).is_empty());
+        assert!(long_pauses(&[]).is_empty());
+    }
+
+    #[test]
+    fn stage_times_should_follow_solved_stages() -> Result<(), Box<dyn Error>> {
+        // The first move solves the first row and column, the rest is the final 2x2.
+        let scramble: Board = "1,0,3/4,2,5/7,8,6".parse()?;
+        let moves = parse_moves("waw")?;
+        let times = stage_times(&scramble, &moves, &millis(&[2000, 2500, 3000]));
+
+        assert_eq!(times, millis(&[2000, 0, 1000]));
+
+        Ok(())
+    }
+}
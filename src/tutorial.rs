@@ -0,0 +1,204 @@
+//! A guided walk through the row-by-row method, one stage at a time. The player moves on to the
+//! next stage only once the current one is solved.
+
+use crate::board::Board;
+use crate::method::{progress, stages, Stage, StageKind};
+
+pub struct Tutorial {
+    stages: Vec<Stage>,
+    current: usize,
+}
+
+impl Tutorial {
+    pub fn new(width: usize, height: usize) -> Tutorial {
+        Tutorial {
+            stages: stages(width, height),
+            current: 0,
+        }
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// Index of the stage being taught.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn stage(&self) -> &Stage {
+        &self.stages[self.current]
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.current + 1 == self.stages.len()
+    }
+
+    /// Whether the current stage and all the ones before it are solved.
+    pub fn stage_done(&self, board: &Board) -> bool {
+        progress(&self.stages, board) > self.current
+    }
+
+    /// An earlier stage that moves of the current one broke again.
+    pub fn broken(&self, board: &Board) -> Option<&Stage> {
+        self.stages[..self.current]
+            .iter()
+            .find(|stage| !stage.is_solved(board))
+    }
+
+    /// Moves on to the next stage once the current one is done. Returns whether it did.
+    pub fn advance(&mut self, board: &Board) -> bool {
+        if self.is_last() || !self.stage_done(board) {
+            return false;
+        }
+
+        self.current += 1;
+
+        true
+    }
+
+    /// Tiles of the current stage that aren't in place yet, highlighted on the board.
+    pub fn targets(&self, board: &Board) -> Vec<u16> {
+        let stage = self.stage();
+
+        stage
+            .cells
+            .iter()
+            .filter(|index| board.tiles()[**index] as usize != **index + 1)
+            .map(|index| *index as u16 + 1)
+            .collect()
+    }
+
+    /// Instructions for the next step of the current stage.
+    pub fn prompt(&self, board: &Board) -> Vec<String> {
+        let stage = self.stage();
+
+        if let Some(broken) = self.broken(board) {
+            return vec![
+                format!("Oops, {} isn't solved anymore.", broken.name),
+                String::from("Put its tiles back before going on, solved stages stay untouched."),
+            ];
+        }
+
+        if self.stage_done(board) {
+            let next = if self.is_last() {
+                String::from("That's the whole board, well done!")
+            } else {
+                format!(
+                    "Press Enter to go on with {}.",
+                    self.stages[self.current + 1].name
+                )
+            };
+
+            return vec![format!("{} is done!", capitalize(&stage.name)), next];
+        }
+
+        let tiles = stage.tiles();
+        let next = stage
+            .cells
+            .iter()
+            .position(|index| board.tiles()[*index] as usize != index + 1)
+            .unwrap_or(0);
+
+        if stage.kind == StageKind::Final {
+            return vec![
+                format!("Last stage: tiles {}.", join(&tiles)),
+                String::from(
+                    "Cycle the blank around the block, the tiles rotate with it until they all \
+                     fall into place.",
+                ),
+            ];
+        }
+
+        let (before, beside) = match stage.kind {
+            StageKind::Row => ("left", "just below"),
+            StageKind::Column | StageKind::Final => ("top", "just to the right of"),
+        };
+
+        if next + 2 < tiles.len() {
+            let careful = if next == 0 {
+                ""
+            } else {
+                " without moving the tiles before it"
+            };
+
+            return vec![
+                format!(
+                    "Solve {} from the {}: tiles {}.",
+                    stage.name,
+                    before,
+                    join(&tiles)
+                ),
+                format!("Next, bring tile {} to its place{}.", tiles[next], careful),
+                String::from("Walk the blank around the tile to push it one cell at a time."),
+            ];
+        }
+
+        // The last two tiles of a row or column can't be placed one by one.
+        let (first, last) = (tiles[tiles.len() - 2], tiles[tiles.len() - 1]);
+
+        vec![
+            format!(
+                "The last two tiles, {} and {}, go in together.",
+                first, last
+            ),
+            format!(
+                "Put tile {} in the corner where {} belongs, and tile {} {} it.",
+                first, last, last, beside
+            ),
+            format!(
+                "Then bring the blank to the place of {} and slide both in.",
+                first
+            ),
+        ]
+    }
+}
+
+fn join(tiles: &[u16]) -> String {
+    tiles
+        .iter()
+        .map(|tile| tile.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn tutorial_should_continue_only_after_stage_is_solved() -> Result<(), Box<dyn Error>> {
+        let mut tutorial = Tutorial::new(3, 3);
+        let board: Board = "1,2,3/5,0,6/4,7,8".parse()?;
+
+        assert_eq!(tutorial.targets(&board), Vec::<u16>::new());
+        assert!(tutorial.advance(&board));
+        assert_eq!(tutorial.stage().name, "column 1");
+        assert_eq!(tutorial.targets(&board), vec![4, 7]);
+        assert!(!tutorial.advance(&board));
+        assert!(tutorial.prompt(&board)[0].contains("4 and 7"));
+
+        let broken: Board = "1,0,3/5,2,6/4,7,8".parse()?;
+        assert_eq!(
+            tutorial.broken(&broken).map(|stage| stage.name.as_str()),
+            Some("row 1")
+        );
+
+        let solved = Board::solved(3, 3);
+        assert!(tutorial.advance(&solved));
+        assert!(tutorial.is_last());
+        assert!(!tutorial.advance(&solved));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::helper::{draw_game, draw_header, tile_length, Event, Events, ThemeMode, ThemeSystem};
+use rust_15_puzzle_cli::{
+    game::{replay_move, GameData, GameState},
+    net::{self, Message, MessageReader},
+};
+use std::{error::Error, io, net::TcpStream, time::Instant};
+use termion::{event::Key, raw::IntoRawMode, screen::AlternateScreen};
+use tui::{
+    backend::TermionBackend,
+    layout::{Constraint, Direction, Layout, Margin},
+    Terminal,
+};
+
+/// Follows a game streamed by `play --spectate`, read-only.
+pub fn watch(address: &str) -> Result<(), Box<dyn Error>> {
+    let address = net::with_default_port(address);
+    let mut reader = MessageReader::new(TcpStream::connect(&address)?);
+
+    let mut game_data = match reader.read()? {
+        Some(Message::New(board, difficulty)) => GameData::from_scramble(board, difficulty, None),
+        _ => return Err("The game closed the stream before it started".into()),
+    };
+    let mut status = format!("Watching {}, press 'q' to stop", address);
+
+    // Terminal initialization
+    let stdout = io::stdout().into_raw_mode()?;
+    let stdout = AlternateScreen::from(stdout);
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.hide_cursor()?;
+
+    let events = Events::new();
+    events.forward_messages(reader);
+    let mut theme_system = ThemeSystem::new(ThemeMode::DarkMode);
+
+    loop {
+        let length = tile_length(&game_data.arr_state);
+        let banner = match game_data.game_state {
+            GameState::INIT => Some(String::from("Waiting for the first move...")),
+            GameState::DONE => Some(format!(
+                "Solved! {}s, {} moves",
+                game_data.base_time.as_secs(),
+                game_data.move_count
+            )),
+            _ => None,
+        };
+
+        terminal.draw(|mut f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(4), Constraint::Min(0)].as_ref())
+                .split(f.size());
+            let margin = Margin {
+                horizontal: 10,
+                vertical: 0,
+            };
+
+            draw_header(
+                &mut f,
+                &chunks[0].inner(&margin),
+                &game_data,
+                &status,
+                banner.as_deref(),
+            )
+            .unwrap();
+            draw_game(
+                &mut f,
+                &chunks[1].inner(&margin),
+                &mut game_data,
+                None,
+                length,
+                &theme_system,
+                false,
+                &[],
+            )
+            .unwrap();
+        })?;
+
+        match events.next()? {
+            Event::Input(Key::Char('q')) => break,
+            Event::Input(Key::Char('c')) => {
+                theme_system = theme_system.change_theme();
+            }
+            Event::Remote(message) => match message {
+                Message::New(board, difficulty) => {
+                    game_data = GameData::from_scramble(board, difficulty, None);
+                }
+                Message::Move(operation, time) => replay_move(&mut game_data, operation, time),
+                Message::Pause(time) => {
+                    game_data.base_time = time;
+                    game_data.game_state = GameState::PAUSED;
+                }
+                Message::Resume => {
+                    game_data.start_time = Instant::now();
+                    game_data.game_state = GameState::PLAYING;
+                }
+                Message::Bye => {
+                    status = String::from("The game ended, press 'q' to quit");
+                }
+                Message::Rematch(_) => {}
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}